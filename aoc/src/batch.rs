@@ -0,0 +1,192 @@
+// Batch: running one day over all the inputs of a folder, to check that the
+// solvers work on every puzzle input and not only on the author's one.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::cache::{self, AnswerCache};
+use crate::solver::{DaySolver, Repository, RunReport, RunStatus};
+use crate::table;
+
+
+// Expected answers per input file name. A missing or "-" answer is not checked.
+pub type AnswersMap = HashMap<String, [Option<String>; 2]>;
+
+
+// Answers file syntax, one input per line, the lines starting with '#' are comments:
+//   <input file name> <part 1 answer> [<part 2 answer>]
+// A '#' elsewhere is part of the answers, like in the drawings of day 10.
+pub fn parse_answers_file(content : &str) -> Result<AnswersMap, String> {
+    let mut answers = AnswersMap::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields : Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 2 || fields.len() > 3 {
+            return Err(format!("answers line {}: expected '<input> <part 1> [<part 2>]', got '{}'", line_idx + 1, line));
+        }
+        let to_answer = |field : Option<&&str>| match field {
+            Some(&"-") | None => None,
+            Some(value) => Some(value.to_string()),
+        };
+        answers.insert(fields[0].to_string(), [to_answer(fields.get(1)), to_answer(fields.get(2))]);
+    }
+    Ok(answers)
+}
+
+
+// All the regular files of the folder, sorted by name.
+pub fn list_inputs(inputs_dir : &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(inputs_dir)
+        .map_err(|error| format!("cannot read {}: {}", inputs_dir.display(), error))?;
+    let mut inputs : Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| !file_name(path).starts_with('.'))
+        .collect();
+    inputs.sort();
+    Ok(inputs)
+}
+
+
+pub fn file_name(path : &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+
+// Compares the computed answers with the expected ones.
+// Returns the text for the table and whether everything matched.
+fn check_answers(computed : &[Option<String>; 2], expected : Option<&[Option<String>; 2]>) -> (String, bool) {
+    let Some(expected) = expected else {
+        return ("-".to_string(), true);
+    };
+
+    let mut mismatches = Vec::<String>::new();
+    for part_idx in 0..2 {
+        if let Some(expected_answer) = &expected[part_idx] {
+            if computed[part_idx].as_ref() != Some(expected_answer) {
+                mismatches.push(format!("part {} expected {}", part_idx + 1, table::shorten(expected_answer, 20)));
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        ("ok".to_string(), true)
+    }
+    else {
+        (mismatches.join(", "), false)
+    }
+}
+
+
+pub fn status_text(status : &RunStatus) -> String {
+    match status {
+        RunStatus::Ok => "ok".to_string(),
+        RunStatus::Panicked(message) => format!("PANIC: {}", table::shorten(message, 40)),
+        RunStatus::Failed(message) => format!("FAILED: {}", table::shorten(message, 40)),
+        RunStatus::TimedOut => "TIMEOUT".to_string(),
+    }
+}
+
+
+pub fn elapsed_text(report : &RunReport) -> String {
+    match report.cached {
+        true => "cached".to_string(),
+        false => table::format_duration(report.elapsed),
+    }
+}
+
+
+// Runs the day on every input and prints the table. Returns true if all
+// the runs succeeded and matched the answers file, when given.
+pub fn run_batch(
+    repository : &Repository,
+    day : u32,
+    inputs_dir : &Path,
+    answers_path : Option<&Path>,
+    cache : Option<&AnswerCache>,
+    timeout : Duration) -> Result<bool, String> {
+
+    let answers = match answers_path {
+        Some(path) => parse_answers_file(&fs::read_to_string(path)
+            .map_err(|error| format!("cannot read {}: {}", path.display(), error))?)?,
+        None => AnswersMap::new(),
+    };
+
+    let inputs = list_inputs(inputs_dir)?;
+    if inputs.is_empty() {
+        return Err(format!("no input files in {}", inputs_dir.display()));
+    }
+
+    println!("Building day {}...", day);
+    let solver = DaySolver::build(repository, day)?;
+
+    let mut rows = Vec::<Vec<String>>::new();
+    let mut failures_counter = 0;
+    let mut mismatches_counter = 0;
+    for input_path in &inputs {
+        let name = file_name(input_path);
+        println!("Running day {} on {}...", day, name);
+        let report = cache::run_cached(cache, &solver, day, input_path, timeout);
+
+        let (check, is_matching) = check_answers(&report.answers, answers.get(&name));
+        if report.status != RunStatus::Ok {
+            failures_counter += 1;
+        }
+        else if !is_matching {
+            mismatches_counter += 1;
+        }
+
+        rows.push(vec![
+            name,
+            status_text(&report.status),
+            table::shorten(report.answers[0].as_deref().unwrap_or("-"), 32),
+            table::shorten(report.answers[1].as_deref().unwrap_or("-"), 32),
+            elapsed_text(&report),
+            check,
+        ]);
+    }
+
+    println!();
+    print!("{}", table::format_table(&["input", "status", "part 1", "part 2", "time", "check"], &rows));
+    println!("{} inputs, {} failed, {} with wrong answers.", inputs.len(), failures_counter, mismatches_counter);
+
+    Ok(failures_counter == 0 && mismatches_counter == 0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers_file() {
+        let answers = parse_answers_file("# day 14\nalice.txt 24 93\n  # only part 2\nbob.txt - 12\n\ncarol.txt 7\n").unwrap();
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers["alice.txt"], [Some("24".to_string()), Some("93".to_string())]);
+        assert_eq!(answers["bob.txt"], [None, Some("12".to_string())]);
+        assert_eq!(answers["carol.txt"], [Some("7".to_string()), None]);
+
+        assert!(parse_answers_file("lonely.txt\n").is_err());
+
+        // The drawings of day 10 are made of '#' and '.'.
+        let answers = parse_answers_file("dave.txt 13140 ##..##..|###...###.\n").unwrap();
+        assert_eq!(answers["dave.txt"], [Some("13140".to_string()), Some("##..##..|###...###.".to_string())]);
+    }
+
+    #[test]
+    fn test_check_answers() {
+        let computed = [Some("24".to_string()), Some("93".to_string())];
+        assert_eq!(check_answers(&computed, None), ("-".to_string(), true));
+        assert_eq!(check_answers(&computed, Some(&[Some("24".to_string()), None])), ("ok".to_string(), true));
+        assert_eq!(
+            check_answers(&computed, Some(&[Some("24".to_string()), Some("94".to_string())])),
+            ("part 2 expected 94".to_string(), false));
+    }
+}
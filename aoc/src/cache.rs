@@ -0,0 +1,222 @@
+// Cache: remembering the answers of the days, so that re-running unchanged
+// work (the same binary on the same input) returns instantly.
+//
+// One line per answer in the index file:
+//   <day> <part> <input sha256> <build id> <answer>
+// The build id is the sha256 of the day binary, so any change of the code
+// invalidates the previous answers.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+use crate::solver::{DaySolver, Repository, RunReport, RunStatus};
+
+
+const INDEX_FILE_NAME : &str = "answers.txt";
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub day : u32,
+    pub part : u32,
+    pub input_sha : String,
+    pub build_id : String,
+}
+
+
+pub struct AnswerCache {
+    pub dir : PathBuf,
+}
+
+impl AnswerCache {
+
+    // The AOC_CACHE_DIR variable wins, otherwise it's ".aoc_cache" in the repository.
+    pub fn locate(repository : &Repository) -> AnswerCache {
+        let dir = match std::env::var_os("AOC_CACHE_DIR") {
+            Some(path) => PathBuf::from(path),
+            None => repository.root.join(".aoc_cache"),
+        };
+        AnswerCache { dir }
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE_NAME)
+    }
+
+    // A missing or unreadable index is just an empty cache.
+    fn read_index(&self) -> HashMap<CacheKey, String> {
+        let content = fs::read_to_string(self.index_path()).unwrap_or_default();
+        content.lines().filter_map(parse_index_line).collect()
+    }
+
+    pub fn get(&self, key : &CacheKey) -> Option<String> {
+        self.read_index().remove(key)
+    }
+
+    pub fn insert(&self, key : &CacheKey, answer : &str) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|error| format!("cannot create {}: {}", self.dir.display(), error))?;
+        let mut index_file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.index_path())
+            .map_err(|error| format!("cannot open {}: {}", self.index_path().display(), error))?;
+        writeln!(index_file, "{} {} {} {} {}", key.day, key.part, key.input_sha, key.build_id, answer)
+            .map_err(|error| format!("cannot write {}: {}", self.index_path().display(), error))
+    }
+
+    // Returns the number of answers removed.
+    pub fn clear(&self) -> Result<usize, String> {
+        if !self.index_path().exists() {
+            return Ok(0);
+        }
+        let answers_number = self.read_index().len();
+        fs::remove_file(self.index_path())
+            .map_err(|error| format!("cannot remove {}: {}", self.index_path().display(), error))?;
+        Ok(answers_number)
+    }
+}
+
+
+fn parse_index_line(line : &str) -> Option<(CacheKey, String)> {
+    let mut fields = line.splitn(5, ' ');
+    let key = CacheKey {
+        day : fields.next()?.parse().ok()?,
+        part : fields.next()?.parse().ok()?,
+        input_sha : fields.next()?.to_string(),
+        build_id : fields.next()?.to_string(),
+    };
+    let answer = fields.next()?;
+    Some((key, answer.to_string()))
+}
+
+
+pub fn sha256_hex(bytes : &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+
+fn file_sha(path : &Path) -> Result<String, String> {
+    fs::read(path)
+        .map(|bytes| sha256_hex(&bytes))
+        .map_err(|error| format!("cannot read {}: {}", path.display(), error))
+}
+
+
+// Runs the day through the cache: the answers are looked up first, and the
+// successful runs are stored. Without cache it's just a run.
+pub fn run_cached(
+    cache : Option<&AnswerCache>,
+    solver : &DaySolver,
+    day : u32,
+    input_path : &Path,
+    timeout : Duration) -> RunReport {
+
+    let Some(cache) = cache else {
+        return solver.run(input_path, timeout);
+    };
+    let (input_sha, build_id) = match (file_sha(input_path), file_sha(&solver.binary)) {
+        (Ok(input_sha), Ok(build_id)) => (input_sha, build_id),
+        _ => return solver.run(input_path, timeout),
+    };
+    let make_key = |part : u32| CacheKey { day, part, input_sha : input_sha.clone(), build_id : build_id.clone() };
+
+    // All the answers of a run are stored together, so any cached part means a complete hit.
+    let start_time = Instant::now();
+    let cached_answers = [cache.get(&make_key(1)), cache.get(&make_key(2))];
+    if cached_answers.iter().any(Option::is_some) {
+        return RunReport { status : RunStatus::Ok, answers : cached_answers, elapsed : start_time.elapsed(), cached : true, memory : [None, None] };
+    }
+
+    let report = solver.run(input_path, timeout);
+    if report.status == RunStatus::Ok {
+        for (part_idx, answer) in report.answers.iter().enumerate() {
+            if let Some(answer) = answer {
+                if let Err(message) = cache.insert(&make_key(part_idx as u32 + 1), answer) {
+                    eprintln!("{}", message);
+                }
+            }
+        }
+    }
+    report
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name : &str) -> AnswerCache {
+        let dir = std::env::temp_dir().join(format!("aoc_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        AnswerCache { dir }
+    }
+
+    fn make_key(part : u32, input_sha : &str) -> CacheKey {
+        CacheKey { day : 15, part, input_sha : input_sha.to_string(), build_id : "b1".to_string() }
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_cache_insert_get_clear() {
+        let cache = temp_cache("insert");
+        assert_eq!(cache.get(&make_key(1, "aa")), None);
+
+        cache.insert(&make_key(1, "aa"), "5688618").unwrap();
+        cache.insert(&make_key(2, "aa"), "##..|.##.").unwrap();
+        cache.insert(&make_key(1, "bb"), "answer with spaces").unwrap();
+        assert_eq!(cache.get(&make_key(1, "aa")), Some("5688618".to_string()));
+        assert_eq!(cache.get(&make_key(2, "aa")), Some("##..|.##.".to_string()));
+        assert_eq!(cache.get(&make_key(1, "bb")), Some("answer with spaces".to_string()));
+        assert_eq!(cache.get(&make_key(2, "bb")), None);
+
+        let other_build = CacheKey { build_id : "b2".to_string(), ..make_key(1, "aa") };
+        assert_eq!(cache.get(&other_build), None);
+
+        assert_eq!(cache.clear().unwrap(), 3);
+        assert_eq!(cache.get(&make_key(1, "aa")), None);
+        assert_eq!(cache.clear().unwrap(), 0);
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_cached() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let cache = temp_cache("run");
+        fs::create_dir_all(&cache.dir).unwrap();
+
+        // A fake day counting its runs in a file next to it.
+        let binary = cache.dir.join("fake_day.sh");
+        fs::write(&binary, "#!/bin/sh\necho run >> runs.txt\necho 'Part 1 result is 3.'\n").unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        let input_path = cache.dir.join("input.txt");
+        fs::write(&input_path, "1\n2\n").unwrap();
+        let solver = DaySolver { dir : cache.dir.clone(), binary };
+        let runs_counter = || fs::read_to_string(cache.dir.join("runs.txt")).unwrap().lines().count();
+
+        let report = run_cached(Some(&cache), &solver, 1, &input_path, Duration::from_secs(10));
+        assert_eq!((report.answers[0].as_deref(), report.cached), (Some("3"), false));
+        let report = run_cached(Some(&cache), &solver, 1, &input_path, Duration::from_secs(10));
+        assert_eq!((report.answers[0].as_deref(), report.cached), (Some("3"), true));
+        assert_eq!(runs_counter(), 1);
+
+        // A different input or no cache runs again.
+        fs::write(&input_path, "1\n2\n3\n").unwrap();
+        assert!(!run_cached(Some(&cache), &solver, 1, &input_path, Duration::from_secs(10)).cached);
+        assert!(!run_cached(None, &solver, 1, &input_path, Duration::from_secs(10)).cached);
+        assert_eq!(runs_counter(), 3);
+
+        fs::remove_dir_all(&cache.dir).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+// Client: talking with the Advent of Code website, for the inputs and the answers.
+//
+// The settings come from the environment or from the config file (AOC_CONFIG,
+// or ".aoc_config" in the repository), one "key = value" per line:
+//   session = <session cookie of the website>
+//   base_url = https://adventofcode.com
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::solver::Repository;
+
+
+pub const YEAR : u32 = 2022;
+const DEFAULT_BASE_URL : &str = "https://adventofcode.com";
+const USER_AGENT : &str = "aoc-2022-runner (rust, ureq)";
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub session : Option<String>,
+    pub base_url : Option<String>,
+}
+
+impl Config {
+
+    // The environment variables win over the config file.
+    pub fn load(repository : &Repository) -> Result<Config, String> {
+        let config_path = match env::var_os("AOC_CONFIG") {
+            Some(path) => PathBuf::from(path),
+            None => repository.root.join(".aoc_config"),
+        };
+        let mut config = match fs::read_to_string(&config_path) {
+            Ok(content) => parse_config(&content)
+                .map_err(|message| format!("{}: {}", config_path.display(), message))?,
+            Err(_) => Config::default(),
+        };
+
+        if let Ok(session) = env::var("AOC_SESSION") {
+            config.session = Some(session);
+        }
+        if let Ok(base_url) = env::var("AOC_BASE_URL") {
+            config.base_url = Some(base_url);
+        }
+        Ok(config)
+    }
+}
+
+
+pub fn parse_config(content : &str) -> Result<Config, String> {
+    let mut values = HashMap::<String, String>::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected 'key = value', got '{}'", line_idx + 1, line));
+        };
+        values.insert(key.trim().to_string(), value.trim().to_string());
+    }
+
+    let config = Config {
+        session : values.remove("session"),
+        base_url : values.remove("base_url"),
+    };
+    match values.keys().next() {
+        Some(unknown) => Err(format!("unknown setting '{}'", unknown)),
+        None => Ok(config),
+    }
+}
+
+
+pub struct AocClient {
+    pub base_url : String,
+    session : String,
+    agent : ureq::Agent,
+}
+
+impl AocClient {
+    pub fn new(config : &Config) -> Result<AocClient, String> {
+        let session = config.session.clone()
+            .ok_or("no session token: set AOC_SESSION or 'session' in the config file".to_string())?;
+        let base_url = config.base_url.clone().unwrap_or(DEFAULT_BASE_URL.to_string());
+        let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build();
+        Ok(AocClient { base_url : base_url.trim_end_matches('/').to_string(), session, agent })
+    }
+
+    pub fn day_url(&self, day : u32) -> String {
+        format!("{}/{}/day/{}", self.base_url, YEAR, day)
+    }
+
+    pub fn get(&self, url : &str) -> Result<String, String> {
+        let response = self.agent.get(url)
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", USER_AGENT)
+            .call();
+        read_response(url, response)
+    }
+
+    pub fn post_form(&self, url : &str, form : &[(&str, &str)]) -> Result<String, String> {
+        let response = self.agent.post(url)
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", USER_AGENT)
+            .send_form(form);
+        read_response(url, response)
+    }
+}
+
+
+fn read_response(url : &str, response : Result<ureq::Response, ureq::Error>) -> Result<String, String> {
+    match response {
+        Ok(response) => response.into_string()
+            .map_err(|error| format!("cannot read the response of {}: {}", url, error)),
+        Err(ureq::Error::Status(400, _)) =>
+            Err(format!("{} refused the request (400), the session token is probably expired", url)),
+        Err(ureq::Error::Status(code, _)) => Err(format!("{} answered with status {}", url, code)),
+        Err(error) => Err(format!("cannot reach the website: {}", error)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config("# my settings\nsession = 53616c7465\n\nbase_url=http://localhost:8080/\n").unwrap();
+        assert_eq!(config.session, Some("53616c7465".to_string()));
+        assert_eq!(config.base_url, Some("http://localhost:8080/".to_string()));
+
+        assert!(parse_config("session 1234\n").is_err());
+        assert!(parse_config("year = 2021\n").is_err());
+
+        let client = AocClient::new(&config).unwrap();
+        assert_eq!(client.day_url(5), "http://localhost:8080/2022/day/5");
+        assert!(AocClient::new(&Config::default()).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+// Fetch: downloading the puzzle input and the example of a day into its data folder.
+//
+// The website asks to be gentle, so the files already there are not
+// downloaded again (unless forced) and the same page is never requested
+// twice within the throttle interval.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::client::AocClient;
+use crate::solver::Repository;
+
+
+pub const THROTTLE_INTERVAL : Duration = Duration::from_secs(15 * 60);
+
+
+// Remembers when each url was requested, one "<unix seconds> <url>" per line.
+pub struct Throttle {
+    pub log_path : PathBuf,
+    pub interval : Duration,
+}
+
+impl Throttle {
+    fn now_secs() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+    }
+
+    fn last_request_secs(&self, url : &str) -> Option<u64> {
+        let content = fs::read_to_string(&self.log_path).ok()?;
+        content.lines()
+            .filter_map(|line| line.split_once(' '))
+            .filter(|(_, logged_url)| *logged_url == url)
+            .filter_map(|(secs, _)| secs.parse::<u64>().ok())
+            .max()
+    }
+
+    pub fn check(&self, url : &str) -> Result<(), String> {
+        let Some(last_secs) = self.last_request_secs(url) else {
+            return Ok(());
+        };
+        let elapsed_secs = Self::now_secs().saturating_sub(last_secs);
+        if elapsed_secs < self.interval.as_secs() {
+            return Err(format!("{} was requested {} s ago, wait {} s more",
+                url, elapsed_secs, self.interval.as_secs() - elapsed_secs));
+        }
+        Ok(())
+    }
+
+    pub fn record(&self, url : &str) -> Result<(), String> {
+        if let Some(dir) = self.log_path.parent() {
+            fs::create_dir_all(dir).map_err(|error| format!("cannot create {}: {}", dir.display(), error))?;
+        }
+        let mut content = fs::read_to_string(&self.log_path).unwrap_or_default();
+        content += &format!("{} {}\n", Self::now_secs(), url);
+        fs::write(&self.log_path, content).map_err(|error| format!("cannot write {}: {}", self.log_path.display(), error))
+    }
+}
+
+
+// The example is the first code block after "For example", or the first one at all.
+// The puzzle pages only use a few tags and entities inside the blocks.
+pub fn extract_example(page : &str) -> Option<String> {
+    let search_start = page.find("For example").unwrap_or(0);
+    let block_start = page[search_start..].find("<pre><code>")
+        .map(|position| search_start + position)
+        .or(page.find("<pre><code>"))? + "<pre><code>".len();
+    let block_end = block_start + page[block_start..].find("</code></pre>")?;
+
+    let mut text = String::new();
+    let mut in_tag = false;
+    for character in page[block_start..block_end].chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(character),
+            _ => (),
+        }
+    }
+    Some(text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'").replace("&amp;", "&"))
+}
+
+
+// Downloads one file, unless it's already there.
+fn fetch_file(
+    client : &AocClient,
+    throttle : &Throttle,
+    url : &str,
+    file_path : &Path,
+    force : bool,
+    extract : impl Fn(&str) -> Option<String>) -> Result<(), String> {
+
+    if file_path.exists() && !force {
+        println!("{} is already there, skipped (--force to download it again).", file_path.display());
+        return Ok(());
+    }
+
+    // Recorded before sending, so that the failed requests are throttled too.
+    throttle.check(url)?;
+    throttle.record(url)?;
+    let body = client.get(url)?;
+
+    let Some(content) = extract(&body) else {
+        println!("Nothing found for {} in {}.", file_path.display(), url);
+        return Ok(());
+    };
+    fs::write(file_path, content).map_err(|error| format!("cannot write {}: {}", file_path.display(), error))?;
+    println!("Saved {}.", file_path.display());
+    Ok(())
+}
+
+
+// The input goes in data/input.txt and the example in data/example.txt,
+// the hand-made test.txt files are left alone.
+pub fn fetch_day(repository : &Repository, client : &AocClient, throttle : &Throttle, day : u32, force : bool) -> Result<(), String> {
+    let data_dir = repository.day_dir(day).join("data");
+    fs::create_dir_all(&data_dir).map_err(|error| format!("cannot create {}: {}", data_dir.display(), error))?;
+
+    let day_url = client.day_url(day);
+    fetch_file(client, throttle, &format!("{}/input", day_url), &data_dir.join("input.txt"), force, |body| Some(body.to_string()))?;
+    fetch_file(client, throttle, &day_url, &data_dir.join("example.txt"), force, extract_example)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Config;
+    use crate::mock_server::MockServer;
+
+    const PUZZLE_PAGE : &str = "<article><p>Consider the <code>list</code>:</p><pre><code>ignored</code></pre>\
+        <p>For example:</p>\n<pre><code>    [D]    \n[N] [C]    \n<em>move</em> 1 from 2 to 1 &amp; &lt;done&gt;\n</code></pre></article>";
+
+    fn temp_repository(name : &str) -> Repository {
+        let root = std::env::temp_dir().join(format!("aoc_fetch_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        Repository { root }
+    }
+
+    #[test]
+    fn test_extract_example() {
+        assert_eq!(extract_example(PUZZLE_PAGE), Some("    [D]    \n[N] [C]    \nmove 1 from 2 to 1 & <done>\n".to_string()));
+        assert_eq!(extract_example("<pre><code>1\n2\n</code></pre>"), Some("1\n2\n".to_string()));
+        assert_eq!(extract_example("<p>no code</p>"), None);
+    }
+
+    #[test]
+    fn test_fetch_day() {
+        let repository = temp_repository("day");
+        let server = MockServer::start(vec![(200, "1000\n2000\n".to_string()), (200, PUZZLE_PAGE.to_string())]);
+        let client = AocClient::new(&Config { session : Some("cafe".to_string()), base_url : Some(server.base_url.clone()) }).unwrap();
+        let throttle = Throttle { log_path : repository.root.join("requests.txt"), interval : THROTTLE_INTERVAL };
+
+        fetch_day(&repository, &client, &throttle, 5, false).unwrap();
+        let data_dir = repository.day_dir(5).join("data");
+        assert_eq!(fs::read_to_string(data_dir.join("input.txt")).unwrap(), "1000\n2000\n");
+        assert!(fs::read_to_string(data_dir.join("example.txt")).unwrap().starts_with("    [D]"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("GET /2022/day/5/input "));
+        assert!(requests[0].contains("session=cafe"));
+        assert!(requests[1].starts_with("GET /2022/day/5 "));
+
+        // Again: the files are there, and forcing is throttled. The server gets nothing more.
+        fetch_day(&repository, &client, &throttle, 5, false).unwrap();
+        assert!(fetch_day(&repository, &client, &throttle, 5, true).unwrap_err().contains("wait"));
+        assert_eq!(server.requests().len(), 2);
+
+        fs::remove_dir_all(&repository.root).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let repository = temp_repository("errors");
+        let server = MockServer::start(vec![(400, "Please log in".to_string()), (404, "Not found".to_string())]);
+        let client = AocClient::new(&Config { session : Some("stale".to_string()), base_url : Some(server.base_url.clone()) }).unwrap();
+        let throttle = Throttle { log_path : repository.root.join("requests.txt"), interval : Duration::ZERO };
+
+        assert!(fetch_day(&repository, &client, &throttle, 1, false).unwrap_err().contains("session token"));
+        assert!(fetch_day(&repository, &client, &throttle, 26, false).unwrap_err().contains("404"));
+        assert!(!repository.day_dir(1).join("data").join("input.txt").exists());
+
+        fs::remove_dir_all(&repository.root).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_failure_throttled() {
+        let repository = temp_repository("failure");
+        let server = MockServer::start(vec![(500, "Oops".to_string())]);
+        let client = AocClient::new(&Config { session : Some("cafe".to_string()), base_url : Some(server.base_url.clone()) }).unwrap();
+        let throttle = Throttle { log_path : repository.root.join("requests.txt"), interval : THROTTLE_INTERVAL };
+
+        // Retrying right after a failure doesn't reach the server.
+        assert!(fetch_day(&repository, &client, &throttle, 3, false).unwrap_err().contains("500"));
+        assert!(fetch_day(&repository, &client, &throttle, 3, false).unwrap_err().contains("wait"));
+        assert_eq!(server.requests().len(), 1);
+
+        fs::remove_dir_all(&repository.root).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+// Advent of Code 2022 runner: builds the daily exercises and runs them.
+
+mod batch;
+mod cache;
+mod client;
+mod fetch;
+#[cfg(test)]
+mod mock_server;
+mod run;
+mod solver;
+mod submit;
+mod table;
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use cache::AnswerCache;
+use client::{AocClient, Config};
+use solver::{DaySolver, Repository, RunStatus};
+
+const USAGE : &str = "\
+Usage:
+  aoc run <day|all> [--input <file>] [--timeout <seconds>] [--no-cache] [--memory]
+  aoc batch <day> <inputs folder> [--answers <file>] [--timeout <seconds>] [--no-cache]
+  aoc cache clear
+  aoc fetch <day> [--force] [--base-url <url>]
+  aoc submit <day> <part> [--input <file>] [--base-url <url>] [--no-cache]
+
+The days are looked up in the parent folder of this crate, or in AOC_ROOT if set.
+With --memory the days are built with the count-alloc feature, reporting the peak heap and allocations per part.
+The answers are cached per day, part, input and build in .aoc_cache, or in AOC_CACHE_DIR if set.
+The website session token comes from AOC_SESSION or from 'session = <token>' in .aoc_config (or AOC_CONFIG).";
+
+const DEFAULT_TIMEOUT_SECS : u64 = 300;
+
+
+// Removes "--name <value>" from the arguments, returning the value.
+fn take_option(args : &mut Vec<String>, name : &str) -> Result<Option<String>, String> {
+    let Some(position) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    if position + 1 >= args.len() {
+        return Err(format!("missing value for {}", name));
+    }
+    let value = args.remove(position + 1);
+    args.remove(position);
+    Ok(Some(value))
+}
+
+
+fn parse_day(text : &str) -> Result<u32, String> {
+    match text.parse::<u32>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!("'{}' is not a day between 1 and 25", text)),
+    }
+}
+
+
+fn parse_timeout(args : &mut Vec<String>) -> Result<Duration, String> {
+    match take_option(args, "--timeout")? {
+        Some(text) => text.parse::<f64>()
+            .ok()
+            .filter(|seconds| *seconds > 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or(format!("'{}' is not a valid timeout", text)),
+        None => Ok(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+    }
+}
+
+
+// Removes "--name" from the arguments, true if it was there.
+fn take_switch(args : &mut Vec<String>, name : &str) -> bool {
+    match args.iter().position(|arg| arg == name) {
+        Some(position) => {
+            args.remove(position);
+            true
+        },
+        None => false,
+    }
+}
+
+
+// Makes sure that all the arguments have been consumed.
+fn expect_no_more(args : &[String]) -> Result<(), String> {
+    match args.first() {
+        Some(arg) => Err(format!("unexpected argument '{}'", arg)),
+        None => Ok(()),
+    }
+}
+
+
+// Dispatches the subcommands. Ok(false) means that the command ran but something failed.
+fn execute(mut args : Vec<String>) -> Result<bool, String> {
+    if args.is_empty() {
+        return Err("missing command".to_string());
+    }
+    let command = args.remove(0);
+    let repository = Repository::locate();
+    let answer_cache = AnswerCache::locate(&repository);
+    let cache = match take_switch(&mut args, "--no-cache") {
+        true => None,
+        false => Some(&answer_cache),
+    };
+
+    match command.as_str() {
+        "run" => {
+            let timeout = parse_timeout(&mut args)?;
+            let input_path = take_option(&mut args, "--input")?.map(PathBuf::from);
+            let memory = take_switch(&mut args, "--memory");
+            if args.is_empty() {
+                return Err("missing day".to_string());
+            }
+            let days = match args.remove(0).as_str() {
+                "all" => repository.available_days(),
+                day => vec![parse_day(day)?],
+            };
+            expect_no_more(&args)?;
+            Ok(run::run_days(&repository, &days, input_path.as_deref(), cache, timeout, memory))
+        },
+
+        "batch" => {
+            let timeout = parse_timeout(&mut args)?;
+            let answers_path = take_option(&mut args, "--answers")?.map(PathBuf::from);
+            if args.len() < 2 {
+                return Err("batch needs a day and a folder of inputs".to_string());
+            }
+            let day = parse_day(&args.remove(0))?;
+            let inputs_dir = PathBuf::from(args.remove(0));
+            expect_no_more(&args)?;
+            batch::run_batch(&repository, day, &inputs_dir, answers_path.as_deref(), cache, timeout)
+        },
+
+        "cache" => {
+            if args.first().map(String::as_str) != Some("clear") {
+                return Err("the cache command is 'aoc cache clear'".to_string());
+            }
+            args.remove(0);
+            expect_no_more(&args)?;
+            let answers_number = answer_cache.clear()?;
+            println!("Removed {} cached answers from {}.", answers_number, answer_cache.dir.display());
+            Ok(true)
+        },
+
+        "fetch" => {
+            let force = take_switch(&mut args, "--force");
+            let mut config = Config::load(&repository)?;
+            if let Some(base_url) = take_option(&mut args, "--base-url")? {
+                config.base_url = Some(base_url);
+            }
+            if args.is_empty() {
+                return Err("missing day".to_string());
+            }
+            let day = parse_day(&args.remove(0))?;
+            expect_no_more(&args)?;
+
+            let client = AocClient::new(&config)?;
+            let throttle = fetch::Throttle { log_path : answer_cache.dir.join("requests.txt"), interval : fetch::THROTTLE_INTERVAL };
+            fetch::fetch_day(&repository, &client, &throttle, day, force)?;
+            Ok(true)
+        },
+
+        "submit" => {
+            let mut config = Config::load(&repository)?;
+            if let Some(base_url) = take_option(&mut args, "--base-url")? {
+                config.base_url = Some(base_url);
+            }
+            let input_path = take_option(&mut args, "--input")?.map(PathBuf::from);
+            if args.len() < 2 {
+                return Err("submit needs a day and a part".to_string());
+            }
+            let day = parse_day(&args.remove(0))?;
+            let part = match args.remove(0).as_str() {
+                "1" => 1,
+                "2" => 2,
+                other => return Err(format!("'{}' is not a part, 1 or 2", other)),
+            };
+            expect_no_more(&args)?;
+            let client = AocClient::new(&config)?;
+
+            // Computing the answer first, through the cache like any run.
+            println!("Running day {}...", day);
+            let solver = DaySolver::build(&repository, day)?;
+            let input_path = input_path.unwrap_or(run::default_input(&repository, day));
+            let report = cache::run_cached(cache, &solver, day, &input_path, Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+            if report.status != RunStatus::Ok {
+                return Err(format!("day {} did not run: {}", day, batch::status_text(&report.status)));
+            }
+            let answer = report.answers[part as usize - 1].clone()
+                .ok_or(format!("day {} gave no answer for part {}", day, part))?;
+
+            println!("Submitting {} for day {} part {}...", answer, day, part);
+            let log = submit::AttemptsLog { path : answer_cache.dir.join("attempts.txt") };
+            let verdict = submit::submit_answer(&client, &log, day, part, &answer)?;
+            println!("{}", verdict.describe());
+            Ok(verdict == submit::Verdict::Correct)
+        },
+
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(true)
+        },
+
+        _ => Err(format!("unknown command '{}'", command)),
+    }
+}
+
+
+// Main
+fn main() -> ExitCode {
+    match execute(env::args().skip(1).collect()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_option() {
+        let mut args : Vec<String> = ["14", "--timeout", "5", "inputs"].iter().map(|arg| arg.to_string()).collect();
+        assert_eq!(take_option(&mut args, "--timeout").unwrap(), Some("5".to_string()));
+        assert_eq!(args, vec!["14".to_string(), "inputs".to_string()]);
+        assert_eq!(take_option(&mut args, "--answers").unwrap(), None);
+
+        let mut args = vec!["--answers".to_string()];
+        assert!(take_option(&mut args, "--answers").is_err());
+
+        let mut args = vec!["all".to_string(), "--no-cache".to_string()];
+        assert!(take_switch(&mut args, "--no-cache"));
+        assert!(!take_switch(&mut args, "--no-cache"));
+        assert_eq!(args, vec!["all".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day("7"), Ok(7));
+        assert!(parse_day("0").is_err());
+        assert!(parse_day("26").is_err());
+        assert!(parse_day("all").is_err());
+    }
+}
@@ -0,0 +1,62 @@
+// Mock server: a tiny local HTTP server for the tests of the client, serving
+// canned responses in order and recording the requests it received.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+
+pub struct MockServer {
+    pub base_url : String,
+    requests : Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+
+    // One (status, body) response per connection, the server stops after the last one.
+    pub fn start(responses : Vec<(u16, String)>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+
+        let recorded_requests = Arc::clone(&requests);
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+
+                // Headers until the empty line, then the body if any.
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    request += &line;
+                }
+                let mut request_body = vec![0; content_length];
+                let _ = reader.read_exact(&mut request_body);
+                request += "\r\n";
+                request += &String::from_utf8_lossy(&request_body);
+                recorded_requests.lock().unwrap().push(request);
+
+                let response = format!("HTTP/1.1 {} Mock\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body);
+                let _ = reader.get_mut().write_all(response.as_bytes());
+            }
+        });
+
+        MockServer { base_url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,86 @@
+// Run: solving one or all the days on their puzzle input.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::batch::{elapsed_text, status_text};
+use crate::cache::{self, AnswerCache};
+use crate::solver::{DaySolver, MemoryReport, Repository, RunStatus};
+use crate::table;
+
+
+// Runs the given days and prints a table with the results. Returns true if all succeeded.
+// With memory, the days are built counting their allocations and the cache is
+// skipped, since the cached answers have no figures.
+pub fn run_days(
+    repository : &Repository,
+    days : &[u32],
+    input_path : Option<&Path>,
+    cache : Option<&AnswerCache>,
+    timeout : Duration,
+    memory : bool) -> bool {
+
+    let mut rows = Vec::<Vec<String>>::new();
+    let mut all_ok = true;
+    for &day in days {
+        println!("Running day {}...", day);
+        let built = match memory {
+            true => DaySolver::build_counting(repository, day),
+            false => DaySolver::build(repository, day),
+        };
+        let solver = match built {
+            Ok(solver) => solver,
+            Err(message) => {
+                eprintln!("{}", message);
+                let mut row = vec![day.to_string(), "BUILD FAILED".to_string(), "-".to_string(), "-".to_string(), "-".to_string()];
+                if memory {
+                    row.extend(memory_cells(&[None, None]));
+                }
+                rows.push(row);
+                all_ok = false;
+                continue;
+            },
+        };
+
+        let input_path = match input_path {
+            Some(path) => path.to_path_buf(),
+            None => default_input(repository, day),
+        };
+        let report = cache::run_cached(if memory {None} else {cache}, &solver, day, &input_path, timeout);
+        all_ok &= report.status == RunStatus::Ok;
+
+        let mut row = vec![
+            day.to_string(),
+            status_text(&report.status),
+            table::shorten(report.answers[0].as_deref().unwrap_or("-"), 32),
+            table::shorten(report.answers[1].as_deref().unwrap_or("-"), 32),
+            elapsed_text(&report),
+        ];
+        if memory {
+            row.extend(memory_cells(&report.memory));
+        }
+        rows.push(row);
+    }
+
+    let mut headers = vec!["day", "status", "part 1", "part 2", "time"];
+    if memory {
+        headers.extend(["peak 1", "allocs 1", "peak 2", "allocs 2"]);
+    }
+    println!();
+    print!("{}", table::format_table(&headers, &rows));
+    all_ok
+}
+
+
+// Peak and allocations of each part, "-" when the day didn't report them.
+fn memory_cells(memory : &[Option<MemoryReport>; 2]) -> Vec<String> {
+    memory.iter().flat_map(|report| match report {
+        Some(report) => [table::format_bytes(report.peak_bytes), report.allocations.to_string()],
+        None => ["-".to_string(), "-".to_string()],
+    }).collect()
+}
+
+
+pub fn default_input(repository : &Repository, day : u32) -> PathBuf {
+    repository.day_dir(day).join("data").join("input.txt")
+}
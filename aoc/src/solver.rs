@@ -0,0 +1,304 @@
+// Solver: building and running the daily exercises as separate processes.
+//
+// Every exercise is its own crate, taking the input file as first argument and
+// printing "Part N result is X." at the end. Running them as processes means
+// that a panic or an endless loop in one day can't take the runner down.
+
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+
+// The folder containing all the "exercise_N" crates.
+pub struct Repository {
+    pub root : PathBuf,
+}
+
+impl Repository {
+
+    // The AOC_ROOT variable wins, otherwise it's the parent of this crate.
+    pub fn locate() -> Repository {
+        let root = match env::var_os("AOC_ROOT") {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf(),
+        };
+        Repository { root }
+    }
+
+    pub fn day_dir(&self, day : u32) -> PathBuf {
+        self.root.join(format!("exercise_{}", day))
+    }
+
+    // All the days that have a crate, in order.
+    pub fn available_days(&self) -> Vec<u32> {
+        (1..=25)
+            .filter(|day| self.day_dir(*day).join("Cargo.toml").is_file())
+            .collect()
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RunStatus {
+    Ok,
+    Panicked(String),
+    Failed(String),
+    TimedOut,
+}
+
+// The heap figures of a part, printed by the days built with the "count-alloc" feature.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemoryReport {
+    pub peak_bytes : u64,
+    pub allocations : u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct RunReport {
+    pub status : RunStatus,
+    pub answers : [Option<String>; 2],
+    pub elapsed : Duration,
+    pub cached : bool, // The answers come from the cache, the day did not run.
+    pub memory : [Option<MemoryReport>; 2],
+}
+
+
+// A built day, ready to be executed on any input.
+pub struct DaySolver {
+    pub dir : PathBuf,
+    pub binary : PathBuf,
+}
+
+impl DaySolver {
+
+    // Building in release mode, the brute force days are painful otherwise.
+    pub fn build(repository : &Repository, day : u32) -> Result<DaySolver, String> {
+        DaySolver::build_with_features(repository, day, &[], "target")
+    }
+
+    // The build counting the allocations goes in its own folder, so that it
+    // doesn't replace the normal one at each switch.
+    pub fn build_counting(repository : &Repository, day : u32) -> Result<DaySolver, String> {
+        DaySolver::build_with_features(repository, day, &["count-alloc"], "target/count-alloc")
+    }
+
+    fn build_with_features(repository : &Repository, day : u32, features : &[&str], target_subdir : &str) -> Result<DaySolver, String> {
+        let dir = repository.day_dir(day);
+        if !dir.join("Cargo.toml").is_file() {
+            return Err(format!("day {} has no crate in {}", day, dir.display()));
+        }
+
+        let target_dir = dir.join(target_subdir);
+        let mut command = Command::new(env::var("CARGO").unwrap_or("cargo".to_string()));
+        command.args(["build", "--release", "--quiet", "--target-dir"]).arg(&target_dir);
+        if !features.is_empty() {
+            command.args(["--features", &features.join(",")]);
+        }
+        let output = command
+            .current_dir(&dir)
+            .output()
+            .map_err(|error| format!("could not launch cargo: {}", error))?;
+        if !output.status.success() {
+            return Err(format!("build of day {} failed:\n{}", day, String::from_utf8_lossy(&output.stderr)));
+        }
+
+        let binary = target_dir
+            .join("release")
+            .join(format!("exercise_{}{}", day, env::consts::EXE_SUFFIX));
+        Ok(DaySolver { dir, binary })
+    }
+
+    // Runs the day on an input file. The working directory is the crate one,
+    // as the days expect to find their "data" folder there.
+    pub fn run(&self, input_path : &Path, timeout : Duration) -> RunReport {
+        let input_path = match fs::canonicalize(input_path) {
+            Ok(path) => path,
+            Err(error) => return RunReport {
+                status : RunStatus::Failed(format!("cannot open {}: {}", input_path.display(), error)),
+                answers : [None, None],
+                elapsed : Duration::ZERO,
+                cached : false,
+                memory : [None, None],
+            },
+        };
+
+        let mut command = Command::new(&self.binary);
+        command.arg(input_path).current_dir(&self.dir);
+        run_process(command, timeout)
+    }
+}
+
+
+// Spawns the command and waits for it, killing it after the timeout.
+// The outputs are drained on separate threads, so that chatty days can't fill the pipes.
+pub fn run_process(mut command : Command, timeout : Duration) -> RunReport {
+    let start_time = Instant::now();
+    let mut child = match command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(error) => return RunReport {
+            status : RunStatus::Failed(format!("could not start: {}", error)),
+            answers : [None, None],
+            elapsed : Duration::ZERO,
+            cached : false,
+            memory : [None, None],
+        },
+    };
+
+    let drain = |mut pipe : Box<dyn Read + Send>| thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        String::from_utf8_lossy(&buffer).to_string()
+    });
+    let stdout_thread = drain(Box::new(child.stdout.take().unwrap()));
+    let stderr_thread = drain(Box::new(child.stderr.take().unwrap()));
+
+    // Polling, since there is no wait with timeout in the std library.
+    let exit_status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if start_time.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            },
+            Ok(None) => thread::sleep(Duration::from_millis(5)),
+            Err(_) => break None,
+        }
+    };
+    let elapsed = start_time.elapsed();
+
+    let stdout = stdout_thread.join().unwrap_or_default();
+    let stderr = stderr_thread.join().unwrap_or_default();
+    let answers = parse_answers(&stdout);
+    let memory = parse_memory(&stdout);
+
+    let status = match exit_status {
+        None => RunStatus::TimedOut,
+        Some(status) if status.success() => RunStatus::Ok,
+        Some(status) => match panic_message(&stderr) {
+            Some(message) => RunStatus::Panicked(message),
+            None => RunStatus::Failed(format!("exited with {}", status)),
+        },
+    };
+
+    RunReport { status, answers, elapsed, cached : false, memory }
+}
+
+
+// Extracts the answers from the lines "Part N result is X.".
+// Multi-line answers (like the drawings of day 10) are joined with '|'.
+pub fn parse_answers(output : &str) -> [Option<String>; 2] {
+    let mut answers = [None, None];
+    let lines : Vec<&str> = output.lines().collect();
+    for (part_idx, answer) in answers.iter_mut().enumerate() {
+        let marker = format!("Part {} result is", part_idx + 1);
+        let Some(start_idx) = lines.iter().rposition(|line| line.trim_start().starts_with(&marker)) else {
+            continue;
+        };
+
+        // The answer continues on the next lines until the next result or the end,
+        // and the sentence is closed by the final dot.
+        let mut answer_lines = vec![&lines[start_idx].trim_start()[marker.len()..]];
+        answer_lines.extend(lines[start_idx + 1..].iter()
+            .take_while(|line| !line.trim_start().starts_with("Part ")));
+        let block = answer_lines.join("\n");
+        let block = block.trim();
+        let joined = block.strip_suffix('.').unwrap_or(block)
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>()
+            .join("|");
+        if !joined.is_empty() {
+            *answer = Some(joined);
+        }
+    }
+    answers
+}
+
+
+// Extracts the lines "Part N memory: peak X bytes, Y allocations.".
+pub fn parse_memory(output : &str) -> [Option<MemoryReport>; 2] {
+    let mut reports = [None, None];
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("Part ") else {
+            continue;
+        };
+        let Some((part, figures)) = rest.split_once(" memory: peak ") else {
+            continue;
+        };
+        let Some((peak_bytes, allocations)) = figures.split_once(" bytes, ") else {
+            continue;
+        };
+        let allocations = allocations.strip_suffix(" allocations.").unwrap_or(allocations);
+        if let (Ok(part @ 1..=2), Ok(peak_bytes), Ok(allocations)) = (part.parse::<usize>(), peak_bytes.parse(), allocations.parse()) {
+            reports[part - 1] = Some(MemoryReport { peak_bytes, allocations });
+        }
+    }
+    reports
+}
+
+
+// The message of a Rust panic is on the line after "panicked at".
+fn panic_message(stderr : &str) -> Option<String> {
+    let mut lines = stderr.lines();
+    lines.find(|line| line.contains("panicked at"))?;
+    Some(lines.next().unwrap_or("").trim().to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let output = "Welcome!\nread 5 lines from input\nPart 1 result is 24.\nPart 2 result is 93.\n";
+        assert_eq!(parse_answers(output), [Some("24".to_string()), Some("93".to_string())]);
+
+        let output = "Part 1 result is 1.\nPart 2 result is \n##..\n.##.\n.\n";
+        assert_eq!(parse_answers(output)[1], Some("##..|.##.".to_string()));
+
+        assert_eq!(parse_answers("Part 1 result is QGTHFZBHV.\n"), [Some("QGTHFZBHV".to_string()), None]);
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        let output = "Part 1 memory: peak 367740 bytes, 7793919 allocations.\nPart 2 memory: peak 814565 bytes, 12 allocations.\nPart 1 result is 3179.\n";
+        assert_eq!(parse_memory(output), [
+            Some(MemoryReport { peak_bytes : 367740, allocations : 7793919 }),
+            Some(MemoryReport { peak_bytes : 814565, allocations : 12 })]);
+        assert_eq!(parse_memory("Part 1 result is 3179.\nPart 3 memory: peak 1 bytes, 1 allocations.\n"), [None, None]);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let stderr = "\nthread 'main' (42) panicked at src/main.rs:272:5:\nassertion failed: found_slots.len() == 1\nnote: run with `RUST_BACKTRACE=1`";
+        assert_eq!(panic_message(stderr), Some("assertion failed: found_slots.len() == 1".to_string()));
+        assert_eq!(panic_message("nothing to see"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo 'Part 1 result is 3.'; echo 'Part 2 result is 4.'"]);
+        let report = run_process(command, Duration::from_secs(10));
+        assert_eq!(report.status, RunStatus::Ok);
+        assert_eq!(report.answers, [Some("3".to_string()), Some("4".to_string())]);
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "exec sleep 10"]);
+        let report = run_process(command, Duration::from_millis(100));
+        assert_eq!(report.status, RunStatus::TimedOut);
+        assert!(report.elapsed < Duration::from_secs(5));
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "exit 3"]);
+        assert!(matches!(run_process(command, Duration::from_secs(10)).status, RunStatus::Failed(_)));
+    }
+}
@@ -0,0 +1,253 @@
+// Submit: sending an answer to the website and keeping track of the attempts.
+//
+// Every verdict is recorded locally, one "<day> <part> <verdict> <answer>" per
+// line, so that a wrong answer is never sent twice and the numbers already
+// known to be too high or too low are refused before reaching the website.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::client::AocClient;
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    Wait(u64),       // Seconds to wait before the next submission.
+    AlreadySolved,   // The website doesn't expect this part anymore.
+    Unknown(String), // The text of the page, when it's none of the above.
+}
+
+impl Verdict {
+
+    // The name in the attempts file, only for the verdicts about the answer.
+    fn record_name(&self) -> Option<&'static str> {
+        match self {
+            Verdict::Correct => Some("correct"),
+            Verdict::TooHigh => Some("too_high"),
+            Verdict::TooLow => Some("too_low"),
+            Verdict::Wrong => Some("wrong"),
+            _ => None,
+        }
+    }
+
+    fn from_record_name(name : &str) -> Option<Verdict> {
+        match name {
+            "correct" => Some(Verdict::Correct),
+            "too_high" => Some(Verdict::TooHigh),
+            "too_low" => Some(Verdict::TooLow),
+            "wrong" => Some(Verdict::Wrong),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Verdict::Correct => "That's the right answer!".to_string(),
+            Verdict::TooHigh => "Wrong answer, too high.".to_string(),
+            Verdict::TooLow => "Wrong answer, too low.".to_string(),
+            Verdict::Wrong => "Wrong answer.".to_string(),
+            Verdict::Wait(seconds) => format!("Submitted too recently, wait {} s before trying again.", seconds),
+            Verdict::AlreadySolved => "This part is not expected, it's probably solved already.".to_string(),
+            Verdict::Unknown(text) => format!("Unexpected response: {}", text),
+        }
+    }
+}
+
+
+// Only the text of the page matters, the tags are dropped.
+fn page_text(page : &str) -> String {
+    let article = match (page.find("<article>"), page.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &page[start..end],
+        _ => page,
+    };
+    let mut text = String::new();
+    let mut in_tag = false;
+    for character in article.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(character),
+            _ => (),
+        }
+    }
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+
+// "You have 1m 23s left to wait." is 83 seconds.
+fn parse_wait_seconds(text : &str) -> Option<u64> {
+    let before = &text[..text.find(" left to wait")?];
+    let mut seconds = 0;
+    for token in before.rsplit(' ').take_while(|token| token.ends_with('s') || token.ends_with('m')) {
+        let (number, unit) = token.split_at(token.len() - 1);
+        seconds += number.parse::<u64>().ok()? * if unit == "m" {60} else {1};
+    }
+    Some(seconds)
+}
+
+
+pub fn parse_verdict(page : &str) -> Verdict {
+    let text = page_text(page);
+    if text.contains("That's the right answer") {
+        Verdict::Correct
+    }
+    else if text.contains("That's not the right answer") {
+        if text.contains("your answer is too high") {
+            Verdict::TooHigh
+        }
+        else if text.contains("your answer is too low") {
+            Verdict::TooLow
+        }
+        else {
+            Verdict::Wrong
+        }
+    }
+    else if text.contains("You gave an answer too recently") {
+        Verdict::Wait(parse_wait_seconds(&text).unwrap_or(60))
+    }
+    else if text.contains("You don't seem to be solving the right level") {
+        Verdict::AlreadySolved
+    }
+    else {
+        Verdict::Unknown(text)
+    }
+}
+
+
+pub struct AttemptsLog {
+    pub path : PathBuf,
+}
+
+impl AttemptsLog {
+    pub fn attempts(&self, day : u32, part : u32) -> Vec<(Verdict, String)> {
+        let content = fs::read_to_string(&self.path).unwrap_or_default();
+        content.lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, ' ');
+                let line_day = fields.next()?.parse::<u32>().ok()?;
+                let line_part = fields.next()?.parse::<u32>().ok()?;
+                let verdict = Verdict::from_record_name(fields.next()?)?;
+                let answer = fields.next()?.to_string();
+                (line_day == day && line_part == part).then_some((verdict, answer))
+            })
+            .collect()
+    }
+
+    pub fn record(&self, day : u32, part : u32, verdict : &Verdict, answer : &str) -> Result<(), String> {
+        let Some(name) = verdict.record_name() else {
+            return Ok(());
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|error| format!("cannot create {}: {}", dir.display(), error))?;
+        }
+        let mut content = fs::read_to_string(&self.path).unwrap_or_default();
+        content += &format!("{} {} {} {}\n", day, part, name, answer);
+        fs::write(&self.path, content).map_err(|error| format!("cannot write {}: {}", self.path.display(), error))
+    }
+}
+
+
+// Refuses the answers that the previous attempts already rule out.
+pub fn check_attempt(attempts : &[(Verdict, String)], answer : &str) -> Result<(), String> {
+    let number = answer.parse::<i128>().ok();
+    for (verdict, previous) in attempts {
+        let previous_number = previous.parse::<i128>().ok();
+        match verdict {
+            Verdict::Correct if previous == answer => return Err(format!("{} was already accepted", answer)),
+            Verdict::Correct => return Err(format!("this part was already solved with {}", previous)),
+            _ if previous == answer => return Err(format!("{} was already sent and was wrong", answer)),
+            Verdict::TooHigh if number.is_some() && number >= previous_number =>
+                return Err(format!("{} is too high, {} already was", answer, previous)),
+            Verdict::TooLow if number.is_some() && number <= previous_number =>
+                return Err(format!("{} is too low, {} already was", answer, previous)),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+
+pub fn submit_answer(client : &AocClient, log : &AttemptsLog, day : u32, part : u32, answer : &str) -> Result<Verdict, String> {
+    if answer.is_empty() || answer.contains(char::is_whitespace) {
+        return Err(format!("'{}' doesn't look like an answer", answer));
+    }
+    check_attempt(&log.attempts(day, part), answer)?;
+
+    let page = client.post_form(&format!("{}/answer", client.day_url(day)), &[("level", &part.to_string()), ("answer", answer)])?;
+    let verdict = parse_verdict(&page);
+    log.record(day, part, &verdict, answer)?;
+    Ok(verdict)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Config;
+    use crate::mock_server::MockServer;
+
+    const RIGHT_PAGE : &str = "<main><article><p>That's the right answer!  You are <em>one gold star</em> closer.</p></article></main>";
+    const TOO_HIGH_PAGE : &str = "<main><article><p>That's not the right answer; your answer is too high.  \
+        If you're stuck, make sure you're using the full input data. Please wait one minute before trying again.</p></article></main>";
+    const WAIT_PAGE : &str = "<main><article><p>You gave an answer too recently; you have to wait after submitting an answer \
+        before trying again.  You have 1m 23s left to wait. <a href=\"/2022/day/1\">[Return to Day 1]</a></p></article></main>";
+
+    #[test]
+    fn test_parse_verdict() {
+        assert_eq!(parse_verdict(RIGHT_PAGE), Verdict::Correct);
+        assert_eq!(parse_verdict(TOO_HIGH_PAGE), Verdict::TooHigh);
+        assert_eq!(parse_verdict("<article><p>That's not the right answer; your answer is too low.</p></article>"), Verdict::TooLow);
+        assert_eq!(parse_verdict("<article><p>That's not the right answer.</p></article>"), Verdict::Wrong);
+        assert_eq!(parse_verdict(WAIT_PAGE), Verdict::Wait(83));
+        assert_eq!(parse_verdict("<article><p>You gave an answer too recently. You have 38s left to wait.</p></article>"), Verdict::Wait(38));
+        assert_eq!(parse_verdict("<article><p>You don't seem to be solving the right level.</p></article>"), Verdict::AlreadySolved);
+        assert_eq!(parse_verdict("<article><p>Hello</p></article>"), Verdict::Unknown("Hello".to_string()));
+    }
+
+    #[test]
+    fn test_check_attempt() {
+        let attempts = vec![(Verdict::TooHigh, "500".to_string()), (Verdict::TooLow, "100".to_string()), (Verdict::Wrong, "ABC".to_string())];
+        assert!(check_attempt(&attempts, "300").is_ok());
+        assert!(check_attempt(&attempts, "500").is_err());
+        assert!(check_attempt(&attempts, "650").is_err());
+        assert!(check_attempt(&attempts, "99").is_err());
+        assert!(check_attempt(&attempts, "ABC").is_err());
+        assert!(check_attempt(&attempts, "ABD").is_ok());
+        assert!(check_attempt(&[(Verdict::Correct, "42".to_string())], "43").is_err());
+    }
+
+    #[test]
+    fn test_submit_answer() {
+        let log_dir = std::env::temp_dir().join(format!("aoc_submit_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&log_dir);
+        let log = AttemptsLog { path : log_dir.join("attempts.txt") };
+        let server = MockServer::start(vec![
+            (200, TOO_HIGH_PAGE.to_string()),
+            (200, WAIT_PAGE.to_string()),
+            (200, RIGHT_PAGE.to_string())]);
+        let client = AocClient::new(&Config { session : Some("cafe".to_string()), base_url : Some(server.base_url.clone()) }).unwrap();
+
+        assert_eq!(submit_answer(&client, &log, 1, 2, "70000").unwrap(), Verdict::TooHigh);
+        let requests = server.requests();
+        assert!(requests[0].starts_with("POST /2022/day/1/answer "));
+        assert!(requests[0].ends_with("level=2&answer=70000"));
+
+        // Ruled out locally, the server sees nothing.
+        assert!(submit_answer(&client, &log, 1, 2, "70000").is_err());
+        assert!(submit_answer(&client, &log, 1, 2, "80000").is_err());
+        assert_eq!(server.requests().len(), 1);
+
+        // Waiting is not an attempt: the same answer can be sent again.
+        assert_eq!(submit_answer(&client, &log, 1, 2, "45000").unwrap(), Verdict::Wait(83));
+        assert_eq!(submit_answer(&client, &log, 1, 2, "45000").unwrap(), Verdict::Correct);
+        assert_eq!(log.attempts(1, 2), vec![(Verdict::TooHigh, "70000".to_string()), (Verdict::Correct, "45000".to_string())]);
+        assert!(log.attempts(1, 1).is_empty());
+        assert!(submit_answer(&client, &log, 1, 2, "45001").is_err());
+
+        fs::remove_dir_all(&log_dir).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+// Table: plain text tables for the reports of the runner.
+
+use std::time::Duration;
+
+
+// Left-aligned columns, sized on the widest cell.
+pub fn format_table(headers : &[&str], rows : &[Vec<String>]) -> String {
+    let mut widths : Vec<usize> = headers.iter().map(|header| header.chars().count()).collect();
+    for row in rows {
+        for (col_idx, cell) in row.iter().enumerate() {
+            widths[col_idx] = widths[col_idx].max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells : Vec<&str>| {
+        cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out_string = format_row(headers.to_vec());
+    out_string += "\n";
+    out_string += &widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<String>>().join("-+-");
+    out_string += "\n";
+    for row in rows {
+        out_string += &format_row(row.iter().map(|cell| cell.as_str()).collect());
+        out_string += "\n";
+    }
+    out_string
+}
+
+
+// Long answers (the day 10 drawing) would make the table unreadable.
+pub fn shorten(text : &str, max_length : usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+    text.chars().take(max_length.saturating_sub(3)).collect::<String>() + "..."
+}
+
+
+pub fn format_duration(duration : Duration) -> String {
+    if duration.as_secs() >= 1 {
+        format!("{:.2} s", duration.as_secs_f64())
+    }
+    else {
+        format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+    }
+}
+
+
+pub fn format_bytes(bytes : u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1048575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let table = format_table(
+            &["day", "answer"],
+            &[vec!["1".to_string(), "68292".to_string()], vec!["10".to_string(), "x".to_string()]]);
+        assert_eq!(table, "day | answer\n----+-------\n1   | 68292\n10  | x\n");
+    }
+
+    #[test]
+    fn test_shorten_and_durations() {
+        assert_eq!(shorten("abcdef", 10), "abcdef");
+        assert_eq!(shorten("abcdefghijkl", 8), "abcde...");
+        assert_eq!(format_duration(Duration::from_millis(1520)), "1.52 s");
+        assert_eq!(format_duration(Duration::from_micros(2500)), "2.5 ms");
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(367740), "359.1 KiB");
+        assert_eq!(format_bytes(3 * 1048576), "3.0 MiB");
+    }
+}
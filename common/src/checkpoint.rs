@@ -0,0 +1,117 @@
+// Checkpoint: periodic saving of the long brute-force loops, so that an
+// interrupted run can continue where it stopped. The checkpoints use the
+// snapshot format, holding the next loop index and the results so far.
+//   --checkpoint <file>         writes the checkpoint while looping
+//   --checkpoint-every <N>      how many iterations between two writes
+//   --resume <file>             continues from the checkpoint (and keeps updating it)
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::DayArgs;
+use crate::snapshot::{self, Snapshot};
+
+
+#[derive(Clone, Debug)]
+pub struct CheckpointOptions {
+    pub path : Option<PathBuf>,
+    pub resume_from : Option<PathBuf>,
+    pub interval : u64,
+}
+
+impl CheckpointOptions {
+    pub fn disabled() -> CheckpointOptions {
+        CheckpointOptions { path : None, resume_from : None, interval : u64::MAX }
+    }
+
+    pub fn from_args(args : &mut DayArgs, default_interval : u64) -> CheckpointOptions {
+        let resume_from = args.take_option("--resume", 1).map(|values| PathBuf::from(&values[0]));
+        let path = args.take_option("--checkpoint", 1).map(|values| PathBuf::from(&values[0]))
+            .or(resume_from.clone());
+        let interval = match args.take_option("--checkpoint-every", 1) {
+            Some(values) => values[0].parse::<u64>().ok().filter(|interval| *interval > 0)
+                .unwrap_or_else(|| panic!("invalid checkpoint interval '{}'", values[0])),
+            None => default_interval,
+        };
+        CheckpointOptions { path, resume_from, interval }
+    }
+
+    pub fn load_resume<S : Snapshot>(&self) -> Option<S> {
+        let path = self.resume_from.as_ref()?;
+        let state = snapshot::load(path).unwrap_or_else(|error| panic!("cannot resume from {}: {}", path.display(), error));
+        println!("Resuming from checkpoint {}", path.display());
+        Some(state)
+    }
+
+    // To be called after each iteration, with the index of the next one.
+    // The file is replaced only once fully written, an interruption can't leave half a checkpoint.
+    pub fn save_if_due<S : Snapshot>(&self, next_index : u64, make_state : impl FnOnce() -> S) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if !next_index.is_multiple_of(self.interval) {
+            return;
+        }
+
+        let temp_path = path.with_extension("tmp");
+        snapshot::save(&make_state(), &temp_path)
+            .and_then(|_| fs::rename(&temp_path, path))
+            .unwrap_or_else(|error| panic!("cannot write checkpoint {}: {}", path.display(), error));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{SnapshotError, SnapshotReader, SnapshotWriter};
+
+    struct LoopState {
+        next_index : u64,
+        best : u64,
+    }
+
+    impl Snapshot for LoopState {
+        const KIND : &'static str = "loop_state";
+
+        fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+            writer.value("next_index", self.next_index);
+            writer.value("best", self.best);
+        }
+
+        fn read_snapshot(reader : &mut SnapshotReader) -> Result<LoopState, SnapshotError> {
+            Ok(LoopState { next_index : reader.value("next_index")?, best : reader.value("best")? })
+        }
+    }
+
+    // Sums the values of the loop, the checkpoint is the partial sum.
+    fn run_loop(options : &CheckpointOptions, stop_at : u64) -> LoopState {
+        let mut state = options.load_resume().unwrap_or(LoopState { next_index : 0, best : 0 });
+        for index in state.next_index..stop_at {
+            state.best += index * index;
+            state.next_index = index + 1;
+            options.save_if_due(state.next_index, || LoopState { ..state });
+        }
+        state
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let path = std::env::temp_dir().join(format!("common_checkpoint_{}.snap", std::process::id()));
+        let mut args = DayArgs::from_vec(vec!["--checkpoint".to_string(), path.display().to_string(),
+            "--checkpoint-every".to_string(), "10".to_string()]);
+        let options = CheckpointOptions::from_args(&mut args, 1000);
+        assert_eq!(options.interval, 10);
+
+        // Interrupted at 57, the checkpoint is at 50.
+        run_loop(&options, 57);
+        let mut args = DayArgs::from_vec(vec!["--resume".to_string(), path.display().to_string()]);
+        let resume_options = CheckpointOptions::from_args(&mut args, 10);
+        assert_eq!(resume_options.path, Some(path.clone()));
+        let resumed = run_loop(&resume_options, 100);
+        fs::remove_file(&path).unwrap();
+
+        let full = run_loop(&CheckpointOptions::disabled(), 100);
+        assert_eq!((resumed.next_index, resumed.best), (full.next_index, full.best));
+    }
+}
@@ -0,0 +1,68 @@
+// Cli: the command line of the days, "exercise_N [input file] [--options...]".
+
+use std::env;
+
+
+pub struct DayArgs {
+    args : Vec<String>,
+}
+
+impl DayArgs {
+    pub fn from_env() -> DayArgs {
+        DayArgs::from_vec(env::args().skip(1).collect())
+    }
+
+    pub fn from_vec(args : Vec<String>) -> DayArgs {
+        DayArgs { args }
+    }
+
+    // Removes "--name <value>..." from the arguments, returning the values.
+    pub fn take_option(&mut self, name : &str, values_number : usize) -> Option<Vec<String>> {
+        let position = self.args.iter().position(|arg| arg == name)?;
+        if position + values_number >= self.args.len() {
+            panic!("{} expects {} value(s)", name, values_number);
+        }
+        let values = self.args.drain(position..position + values_number + 1).skip(1).collect();
+        Some(values)
+    }
+
+    // Removes "--name" from the arguments, true if it was there.
+    pub fn take_switch(&mut self, name : &str) -> bool {
+        match self.args.iter().position(|arg| arg == name) {
+            Some(position) => {
+                self.args.remove(position);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // What remains after the options is the input file, if any.
+    pub fn input_path(self, default_path : &str) -> String {
+        if let Some(unknown) = self.args.iter().find(|arg| arg.starts_with("--")) {
+            panic!("unknown option {}", unknown);
+        }
+        self.args.into_iter().next().unwrap_or(default_path.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_args(args : &[&str]) -> DayArgs {
+        DayArgs::from_vec(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn test_day_args() {
+        let mut args = make_args(&["--snapshot", "10", "out.snap", "my_input.txt", "--resume", "in.snap"]);
+        assert_eq!(args.take_option("--snapshot", 2), Some(vec!["10".to_string(), "out.snap".to_string()]));
+        assert_eq!(args.take_option("--resume", 1), Some(vec!["in.snap".to_string()]));
+        assert!(!args.take_switch("--verbose"));
+        assert_eq!(args.input_path("./data/input.txt"), "my_input.txt");
+
+        assert_eq!(make_args(&[]).input_path("./data/input.txt"), "./data/input.txt");
+    }
+}
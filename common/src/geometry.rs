@@ -0,0 +1,557 @@
+// Geometry: 2D/3D integer points and grid directions.
+//
+// Coordinates are always signed, so that moving "before" the origin is a
+// perfectly legit operation and the bound checks can be done afterwards,
+// instead of casting back and forth between usize and i32.
+//
+// For 2D the convention is the one of the input files: x is the column and
+// y is the row, growing DOWNWARDS. The few boards that grow upwards (like the
+// tetris one) can use the "_y_up" flavour of the direction deltas.
+
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+
+// The scalar type of the points: any signed primitive integer.
+pub trait Coord:
+    Copy + Ord + Hash + Default + fmt::Debug + fmt::Display +
+    Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self> +
+    AddAssign + SubAssign {
+
+    const ZERO : Self;
+    const ONE : Self;
+
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+
+    // Conversions for indexing dense matrices. Negative values are not valid indexes.
+    fn from_usize(value : usize) -> Self;
+    fn to_usize(self) -> Option<usize>;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {$(
+        impl Coord for $t {
+            const ZERO : Self = 0;
+            const ONE : Self = 1;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn signum(self) -> Self {
+                <$t>::signum(self)
+            }
+
+            fn from_usize(value : usize) -> Self {
+                <$t>::try_from(value).expect("index does not fit in the coordinate type")
+            }
+
+            fn to_usize(self) -> Option<usize> {
+                usize::try_from(self).ok()
+            }
+        }
+    )*};
+}
+impl_coord!(i8, i16, i32, i64, i128, isize);
+
+
+// 2D point
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Debug)]
+pub struct Point2<T : Coord = i32> {
+    pub x : T,
+    pub y : T,
+}
+
+impl<T : Coord> Point2<T> {
+    pub const fn new(x : T, y : T) -> Point2<T> {
+        Point2 { x, y }
+    }
+
+    pub fn zero() -> Point2<T> {
+        Point2::new(T::ZERO, T::ZERO)
+    }
+
+    pub fn from_usize(x : usize, y : usize) -> Point2<T> {
+        Point2::new(T::from_usize(x), T::from_usize(y))
+    }
+
+    // Inverse of index_in: row-major position in a dense matrix of the given width.
+    pub fn from_index(index : usize, width : usize) -> Point2<T> {
+        Point2::from_usize(index % width, index / width)
+    }
+
+    // Sum of the absolute differences along the axes.
+    pub fn manhattan(&self, other : &Point2<T>) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    // Maximum of the absolute differences along the axes (king moves).
+    pub fn chebyshev(&self, other : &Point2<T>) -> T {
+        std::cmp::max((self.x - other.x).abs(), (self.y - other.y).abs())
+    }
+
+    // Unit step (-1, 0 or 1 per axis) towards the direction of the point.
+    pub fn signum(&self) -> Point2<T> {
+        Point2::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn abs(&self) -> Point2<T> {
+        Point2::new(self.x.abs(), self.y.abs())
+    }
+
+    // The next point along a direction, on a grid where y grows downwards.
+    pub fn step(&self, direction : Dir4) -> Point2<T> {
+        *self + direction.delta()
+    }
+
+    pub fn neighbours4(&self) -> [Point2<T>; 4] {
+        Dir4::ALL.map(|direction| self.step(direction))
+    }
+
+    pub fn neighbours8(&self) -> [Point2<T>; 8] {
+        Dir8::ALL.map(|direction| *self + direction.delta())
+    }
+
+    // True if the point lies in the [0, size) rectangle.
+    pub fn is_within(&self, size : &Point2<T>) -> bool {
+        self.x >= T::ZERO && self.y >= T::ZERO && self.x < size.x && self.y < size.y
+    }
+
+    // Row-major index of the point in a dense matrix of the given size, if inside.
+    pub fn index_in(&self, size : &Point2<T>) -> Option<usize> {
+        if !self.is_within(size) {
+            return None;
+        }
+        Some(self.x.to_usize()? + self.y.to_usize()? * size.x.to_usize()?)
+    }
+}
+
+impl<T : Coord> Add for Point2<T> {
+    type Output = Point2<T>;
+    fn add(self, other : Point2<T>) -> Point2<T> {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T : Coord> Sub for Point2<T> {
+    type Output = Point2<T>;
+    fn sub(self, other : Point2<T>) -> Point2<T> {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T : Coord> Neg for Point2<T> {
+    type Output = Point2<T>;
+    fn neg(self) -> Point2<T> {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+impl<T : Coord> Mul<T> for Point2<T> {
+    type Output = Point2<T>;
+    fn mul(self, scale : T) -> Point2<T> {
+        Point2::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl<T : Coord> AddAssign for Point2<T> {
+    fn add_assign(&mut self, other : Point2<T>) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T : Coord> SubAssign for Point2<T> {
+    fn sub_assign(&mut self, other : Point2<T>) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl<T : Coord> From<(T, T)> for Point2<T> {
+    fn from(value : (T, T)) -> Point2<T> {
+        Point2::new(value.0, value.1)
+    }
+}
+
+impl<T : Coord> fmt::Display for Point2<T> {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+
+// 3D point
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Debug)]
+pub struct Point3<T : Coord = i32> {
+    pub x : T,
+    pub y : T,
+    pub z : T,
+}
+
+impl<T : Coord> Point3<T> {
+    pub const fn new(x : T, y : T, z : T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+
+    pub fn zero() -> Point3<T> {
+        Point3::new(T::ZERO, T::ZERO, T::ZERO)
+    }
+
+    // Same as a point with the same value on all axes.
+    pub fn splat(value : T) -> Point3<T> {
+        Point3::new(value, value, value)
+    }
+
+    pub fn manhattan(&self, other : &Point3<T>) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    pub fn chebyshev(&self, other : &Point3<T>) -> T {
+        std::cmp::max(
+            std::cmp::max((self.x - other.x).abs(), (self.y - other.y).abs()),
+            (self.z - other.z).abs())
+    }
+
+    pub fn signum(&self) -> Point3<T> {
+        Point3::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    pub fn abs(&self) -> Point3<T> {
+        Point3::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    // Per-axis minimum and maximum, handy for bounding boxes.
+    pub fn axis_min(&self, other : &Point3<T>) -> Point3<T> {
+        Point3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    pub fn axis_max(&self, other : &Point3<T>) -> Point3<T> {
+        Point3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+
+    // The six points sharing a face with this one.
+    pub fn neighbours6(&self) -> [Point3<T>; 6] {
+        let mut all_points = [*self; 6];
+        for axis in 0..3 {
+            all_points[2 * axis][axis] += T::ONE;
+            all_points[2 * axis + 1][axis] -= T::ONE;
+        }
+        all_points
+    }
+
+    // True if the point lies in the [min, max] box, edges included.
+    pub fn is_within_box(&self, min : &Point3<T>, max : &Point3<T>) -> bool {
+        (0..3).all(|axis| self[axis] >= min[axis] && self[axis] <= max[axis])
+    }
+}
+
+impl<T : Coord> Index<usize> for Point3<T> {
+    type Output = T;
+    fn index(&self, axis : usize) -> &T {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Point3 has only three axes, got {}", axis),
+        }
+    }
+}
+
+impl<T : Coord> IndexMut<usize> for Point3<T> {
+    fn index_mut(&mut self, axis : usize) -> &mut T {
+        match axis {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Point3 has only three axes, got {}", axis),
+        }
+    }
+}
+
+impl<T : Coord> Add for Point3<T> {
+    type Output = Point3<T>;
+    fn add(self, other : Point3<T>) -> Point3<T> {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T : Coord> Sub for Point3<T> {
+    type Output = Point3<T>;
+    fn sub(self, other : Point3<T>) -> Point3<T> {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T : Coord> Neg for Point3<T> {
+    type Output = Point3<T>;
+    fn neg(self) -> Point3<T> {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T : Coord> Mul<T> for Point3<T> {
+    type Output = Point3<T>;
+    fn mul(self, scale : T) -> Point3<T> {
+        Point3::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl<T : Coord> AddAssign for Point3<T> {
+    fn add_assign(&mut self, other : Point3<T>) {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+    }
+}
+
+impl<T : Coord> SubAssign for Point3<T> {
+    fn sub_assign(&mut self, other : Point3<T>) {
+        self.x -= other.x;
+        self.y -= other.y;
+        self.z -= other.z;
+    }
+}
+
+impl<T : Coord> From<[T; 3]> for Point3<T> {
+    fn from(value : [T; 3]) -> Point3<T> {
+        Point3::new(value[0], value[1], value[2])
+    }
+}
+
+impl<T : Coord> fmt::Display for Point3<T> {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+
+// Relative rotations, as in "turn left" or "turn back".
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Turn {
+    Right,
+    Left,
+    Back,
+}
+
+
+// The four grid directions. They are numbered clockwise starting from Right,
+// which is also the "facing" value used by some puzzles.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Dir4 {
+    Right = 0,
+    Down = 1,
+    Left = 2,
+    Up = 3,
+}
+
+impl Dir4 {
+    pub const ALL : [Dir4; 4] = [Dir4::Right, Dir4::Down, Dir4::Left, Dir4::Up];
+
+    pub fn from_index(index : u32) -> Dir4 {
+        Dir4::ALL[(index % 4) as usize]
+    }
+
+    pub fn index(self) -> u32 {
+        self as u32
+    }
+
+    pub fn turn_right(self) -> Dir4 {
+        Dir4::from_index(self.index() + 1)
+    }
+
+    pub fn turn_left(self) -> Dir4 {
+        Dir4::from_index(self.index() + 3)
+    }
+
+    pub fn flip(self) -> Dir4 {
+        Dir4::from_index(self.index() + 2)
+    }
+
+    pub fn turn(self, turn : Turn) -> Dir4 {
+        match turn {
+            Turn::Right => self.turn_right(),
+            Turn::Left => self.turn_left(),
+            Turn::Back => self.flip(),
+        }
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        self == Dir4::Left || self == Dir4::Right
+    }
+
+    // Unit step on a grid where y grows downwards (rows of the input).
+    pub fn delta<T : Coord>(self) -> Point2<T> {
+        match self {
+            Dir4::Right => Point2::new(T::ONE, T::ZERO),
+            Dir4::Down => Point2::new(T::ZERO, T::ONE),
+            Dir4::Left => Point2::new(-T::ONE, T::ZERO),
+            Dir4::Up => Point2::new(T::ZERO, -T::ONE),
+        }
+    }
+
+    // Unit step on a cartesian plane where y grows upwards.
+    pub fn delta_y_up<T : Coord>(self) -> Point2<T> {
+        let delta = self.delta::<T>();
+        Point2::new(delta.x, -delta.y)
+    }
+
+    // The direction of a unit step, if the step is one.
+    pub fn from_delta<T : Coord>(delta : Point2<T>) -> Option<Dir4> {
+        Dir4::ALL.into_iter().find(|direction| direction.delta() == delta)
+    }
+}
+
+
+// The eight directions including diagonals, numbered clockwise from Right.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Dir8 {
+    Right = 0,
+    DownRight = 1,
+    Down = 2,
+    DownLeft = 3,
+    Left = 4,
+    UpLeft = 5,
+    Up = 6,
+    UpRight = 7,
+}
+
+impl Dir8 {
+    pub const ALL : [Dir8; 8] = [
+        Dir8::Right, Dir8::DownRight, Dir8::Down, Dir8::DownLeft,
+        Dir8::Left, Dir8::UpLeft, Dir8::Up, Dir8::UpRight];
+
+    pub fn from_index(index : u32) -> Dir8 {
+        Dir8::ALL[(index % 8) as usize]
+    }
+
+    pub fn index(self) -> u32 {
+        self as u32
+    }
+
+    // 45 degrees turns.
+    pub fn turn_right(self) -> Dir8 {
+        Dir8::from_index(self.index() + 1)
+    }
+
+    pub fn turn_left(self) -> Dir8 {
+        Dir8::from_index(self.index() + 7)
+    }
+
+    pub fn flip(self) -> Dir8 {
+        Dir8::from_index(self.index() + 4)
+    }
+
+    // Relative turns are 90 degrees, as for Dir4.
+    pub fn turn(self, turn : Turn) -> Dir8 {
+        match turn {
+            Turn::Right => Dir8::from_index(self.index() + 2),
+            Turn::Left => Dir8::from_index(self.index() + 6),
+            Turn::Back => self.flip(),
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    pub fn delta<T : Coord>(self) -> Point2<T> {
+        match self {
+            Dir8::Right => Point2::new(T::ONE, T::ZERO),
+            Dir8::DownRight => Point2::new(T::ONE, T::ONE),
+            Dir8::Down => Point2::new(T::ZERO, T::ONE),
+            Dir8::DownLeft => Point2::new(-T::ONE, T::ONE),
+            Dir8::Left => Point2::new(-T::ONE, T::ZERO),
+            Dir8::UpLeft => Point2::new(-T::ONE, -T::ONE),
+            Dir8::Up => Point2::new(T::ZERO, -T::ONE),
+            Dir8::UpRight => Point2::new(T::ONE, -T::ONE),
+        }
+    }
+
+    pub fn delta_y_up<T : Coord>(self) -> Point2<T> {
+        let delta = self.delta::<T>();
+        Point2::new(delta.x, -delta.y)
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(direction : Dir4) -> Dir8 {
+        Dir8::from_index(direction.index() * 2)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point2_arithmetic() {
+        let a = Point2::new(3, -2);
+        let b = Point2::new(-1, 5);
+        assert_eq!(a + b, Point2::new(2, 3));
+        assert_eq!(a - b, Point2::new(4, -7));
+        assert_eq!(-a, Point2::new(-3, 2));
+        assert_eq!(a * 3, Point2::new(9, -6));
+        assert_eq!((a - b).signum(), Point2::new(1, -1));
+
+        let mut c = a;
+        c += b;
+        c -= Point2::new(1, 1);
+        assert_eq!(c, Point2::new(1, 2));
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point2::new(0, 0);
+        let b = Point2::new(3, -4);
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+
+        let c = Point3::new(1, 2, 3);
+        let d = Point3::new(-1, 2, 7);
+        assert_eq!(c.manhattan(&d), 6);
+        assert_eq!(c.chebyshev(&d), 4);
+    }
+
+    #[test]
+    fn test_grid_indexing() {
+        let size = Point2::new(4, 3);
+        assert_eq!(Point2::new(1, 2).index_in(&size), Some(9));
+        assert_eq!(Point2::new(-1, 0).index_in(&size), None);
+        assert_eq!(Point2::new(4, 0).index_in(&size), None);
+        assert_eq!(Point2::<i32>::from_index(9, 4), Point2::new(1, 2));
+    }
+
+    #[test]
+    fn test_dir4_turning() {
+        assert_eq!(Dir4::Up.turn_right(), Dir4::Right);
+        assert_eq!(Dir4::Right.turn_left(), Dir4::Up);
+        assert_eq!(Dir4::Left.turn(Turn::Back), Dir4::Right);
+        for direction in Dir4::ALL {
+            assert_eq!(direction.turn_right().turn_left(), direction);
+            assert_eq!(Dir4::from_delta(direction.delta::<i32>()), Some(direction));
+        }
+        assert_eq!(Point2::new(2, 2).step(Dir4::Up), Point2::new(2, 1));
+        assert_eq!(Dir4::Up.delta_y_up::<i32>(), Point2::new(0, 1));
+    }
+
+    #[test]
+    fn test_dir8_turning() {
+        assert_eq!(Dir8::Up.turn_right(), Dir8::UpRight);
+        assert_eq!(Dir8::UpRight.turn(Turn::Right), Dir8::DownRight);
+        assert_eq!(Dir8::from(Dir4::Left), Dir8::Left);
+        assert!(Dir8::DownLeft.is_diagonal());
+        assert_eq!(Point2::new(0, 0).neighbours8().len(), 8);
+    }
+
+    #[test]
+    fn test_point3_neighbours() {
+        let neighbours = Point3::new(1, 1, 1).neighbours6();
+        assert!(neighbours.contains(&Point3::new(2, 1, 1)));
+        assert!(neighbours.contains(&Point3::new(1, 1, 0)));
+        assert!(neighbours.iter().all(|point| point.manhattan(&Point3::new(1, 1, 1)) == 1));
+    }
+}
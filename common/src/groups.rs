@@ -0,0 +1,59 @@
+// Groups: reading inputs made of blank-line separated groups of lines.
+//
+// The last group doesn't need a blank line after it, the end of the input closes it.
+// Several blank lines in a row don't make empty groups, and lines made only of
+// spaces count as blank.
+
+use std::io::{self, BufRead, Lines};
+
+
+pub struct Groups<B> {
+    lines : Lines<B>,
+}
+
+impl<B : BufRead> Iterator for Groups<B> {
+    type Item = io::Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<String>>> {
+        let mut group = Vec::<String>::new();
+        loop {
+            match self.lines.next() {
+                Some(Ok(line)) if line.trim().is_empty() => {
+                    if !group.is_empty() {
+                        return Some(Ok(group));
+                    }
+                },
+                Some(Ok(line)) => group.push(line),
+                Some(Err(error)) => return Some(Err(error)),
+                None if group.is_empty() => return None,
+                None => return Some(Ok(group)),
+            }
+        }
+    }
+}
+
+
+pub fn groups<B : BufRead>(reader : B) -> Groups<B> {
+    Groups { lines : reader.lines() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(text : &str) -> Vec<Vec<String>> {
+        groups(text.as_bytes()).map(|group| group.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_groups() {
+        let expected = vec![vec!["1".to_string(), "2".to_string()], vec!["3".to_string()]];
+        assert_eq!(read_all("1\n2\n\n3\n"), expected);
+        assert_eq!(read_all("1\n2\n\n3"), expected);
+        assert_eq!(read_all("\n1\n2\n\n\n  \n3\n\n"), expected);
+        assert_eq!(read_all("1\r\n2\r\n\r\n3"), expected);
+        assert!(read_all("").is_empty());
+        assert!(read_all("\n\n").is_empty());
+    }
+}
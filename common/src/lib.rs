@@ -0,0 +1,10 @@
+// Common: utilities shared between the daily exercises.
+
+pub mod checkpoint;
+pub mod cli;
+pub mod geometry;
+pub mod groups;
+pub mod memory;
+pub mod snapshot;
+pub mod stepper;
+pub mod top_k;
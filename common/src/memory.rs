@@ -0,0 +1,157 @@
+// Memory: counting the heap allocations, to find the hungry parts of the days.
+//
+// With the "count-alloc" feature the counting allocator becomes the global one,
+// and report_part prints a line for the runner after each part:
+//   Part N memory: peak <bytes> bytes, <count> allocations.
+// Without the feature nothing is counted and nothing is printed.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AllocStats {
+    pub current_bytes : u64,
+    pub peak_bytes : u64,
+    pub allocations : u64,
+}
+
+
+// The counters of one allocator. The peak and the allocations are counted
+// since the last reset, the current bytes since the start.
+pub struct AllocCounters {
+    current_bytes : AtomicU64,
+    peak_bytes : AtomicU64,
+    allocations : AtomicU64,
+}
+
+impl AllocCounters {
+    pub const fn new() -> AllocCounters {
+        AllocCounters { current_bytes : AtomicU64::new(0), peak_bytes : AtomicU64::new(0), allocations : AtomicU64::new(0) }
+    }
+
+    pub fn record_alloc(&self, size : usize) {
+        let current_bytes = self.current_bytes.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+        self.peak_bytes.fetch_max(current_bytes, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dealloc(&self, size : usize) {
+        self.current_bytes.fetch_sub(size as u64, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            current_bytes : self.current_bytes.load(Ordering::Relaxed),
+            peak_bytes : self.peak_bytes.load(Ordering::Relaxed),
+            allocations : self.allocations.load(Ordering::Relaxed),
+        }
+    }
+
+    // Starts a new measure: the peak goes back to what is currently allocated.
+    pub fn reset(&self) {
+        self.peak_bytes.store(self.current_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.allocations.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Default for AllocCounters {
+    fn default() -> AllocCounters {
+        AllocCounters::new()
+    }
+}
+
+
+pub static COUNTERS : AllocCounters = AllocCounters::new();
+
+
+// The system allocator, counting into COUNTERS.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout : Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            COUNTERS.record_alloc(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer : *mut u8, layout : Layout) {
+        System.dealloc(pointer, layout);
+        COUNTERS.record_dealloc(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout : Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            COUNTERS.record_alloc(layout.size());
+        }
+        pointer
+    }
+
+    // A reallocation counts as one allocation of the new size.
+    unsafe fn realloc(&self, pointer : *mut u8, layout : Layout, new_size : usize) -> *mut u8 {
+        let new_pointer = System.realloc(pointer, layout, new_size);
+        if !new_pointer.is_null() {
+            COUNTERS.record_dealloc(layout.size());
+            COUNTERS.record_alloc(new_size);
+        }
+        new_pointer
+    }
+}
+
+
+#[cfg(feature = "count-alloc")]
+#[global_allocator]
+static GLOBAL_ALLOCATOR : CountingAllocator = CountingAllocator;
+
+
+pub fn format_report(part : u32, stats : &AllocStats) -> String {
+    format!("Part {} memory: peak {} bytes, {} allocations.", part, stats.peak_bytes, stats.allocations)
+}
+
+
+// Prints the figures of the part that just ended and starts counting the next one.
+// The peak of a part includes what the previous parts left allocated.
+pub fn report_part(part : u32) {
+    if cfg!(feature = "count-alloc") {
+        println!("{}", format_report(part, &COUNTERS.stats()));
+        COUNTERS.reset();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let counters = AllocCounters::new();
+        counters.record_alloc(100);
+        counters.record_alloc(50);
+        counters.record_dealloc(100);
+        assert_eq!(counters.stats(), AllocStats { current_bytes : 50, peak_bytes : 150, allocations : 2 });
+
+        counters.reset();
+        counters.record_alloc(20);
+        counters.record_dealloc(20);
+        assert_eq!(counters.stats(), AllocStats { current_bytes : 50, peak_bytes : 70, allocations : 1 });
+        assert_eq!(format_report(2, &counters.stats()), "Part 2 memory: peak 70 bytes, 1 allocations.");
+    }
+
+    #[test]
+    fn test_counting_allocator() {
+        let layout = Layout::from_size_align(4096, 8).unwrap();
+        let allocations_before = COUNTERS.stats().allocations;
+        unsafe {
+            let pointer = CountingAllocator.alloc(layout);
+            assert!(!pointer.is_null());
+            let pointer = CountingAllocator.realloc(pointer, layout, 8192);
+            CountingAllocator.dealloc(pointer, Layout::from_size_align(8192, 8).unwrap());
+        }
+        assert!(COUNTERS.stats().allocations >= allocations_before + 2);
+        assert!(COUNTERS.stats().peak_bytes >= 8192);
+    }
+}
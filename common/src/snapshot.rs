@@ -0,0 +1,281 @@
+// Snapshot: a compact text format to save the state of the simulations, so
+// that they can be resumed later or compared with golden files in the tests.
+//
+//   snapshot <kind>
+//   <key> <value>
+//   <key> <value> <value> ...
+//   <key> <rows number>
+//   <row>
+//   ...
+//
+// The keys are read back in the same order they were written, which keeps the
+// format trivial to parse and the files easy to diff.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::cli::DayArgs;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotError {
+    pub line : usize,
+    pub message : String,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "snapshot line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+
+// Implemented by the states that can be saved and restored.
+pub trait Snapshot : Sized {
+    const KIND : &'static str;
+
+    fn write_snapshot(&self, writer : &mut SnapshotWriter);
+    fn read_snapshot(reader : &mut SnapshotReader) -> Result<Self, SnapshotError>;
+}
+
+
+pub struct SnapshotWriter {
+    out_string : String,
+}
+
+impl SnapshotWriter {
+    fn new(kind : &str) -> SnapshotWriter {
+        SnapshotWriter { out_string : format!("snapshot {}\n", kind) }
+    }
+
+    pub fn value<T : fmt::Display>(&mut self, key : &str, value : T) {
+        self.out_string += &format!("{} {}\n", key, value);
+    }
+
+    pub fn list<T : fmt::Display>(&mut self, key : &str, values : impl IntoIterator<Item = T>) {
+        self.out_string += key;
+        for value in values {
+            self.out_string += &format!(" {}", value);
+        }
+        self.out_string += "\n";
+    }
+
+    // The rows must not contain line breaks.
+    pub fn grid(&mut self, key : &str, rows : &[String]) {
+        self.out_string += &format!("{} {}\n", key, rows.len());
+        for row in rows {
+            self.out_string += row;
+            self.out_string += "\n";
+        }
+    }
+}
+
+
+pub struct SnapshotReader<'a> {
+    lines : std::str::Lines<'a>,
+    line_number : usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn new(text : &'a str, kind : &str) -> Result<SnapshotReader<'a>, SnapshotError> {
+        let mut reader = SnapshotReader { lines : text.lines(), line_number : 0 };
+        let header = reader.next_line()?;
+        if header != format!("snapshot {}", kind) {
+            return Err(reader.error(format!("expected a '{}' snapshot, found '{}'", kind, header)));
+        }
+        Ok(reader)
+    }
+
+    pub fn error(&self, message : String) -> SnapshotError {
+        SnapshotError { line : self.line_number, message }
+    }
+
+    fn next_line(&mut self) -> Result<&'a str, SnapshotError> {
+        self.line_number += 1;
+        self.lines.next().ok_or(SnapshotError { line : self.line_number, message : "unexpected end of snapshot".to_string() })
+    }
+
+    // Reads the line of the key, returning what follows it.
+    fn keyed_line(&mut self, key : &str) -> Result<&'a str, SnapshotError> {
+        let line = self.next_line()?;
+        match line.split_once(' ') {
+            Some((found_key, rest)) if found_key == key => Ok(rest),
+            None if line == key => Ok(""),
+            _ => Err(self.error(format!("expected key '{}', found '{}'", key, line))),
+        }
+    }
+
+    fn parse<T : FromStr>(&self, text : &str) -> Result<T, SnapshotError> {
+        text.parse::<T>().map_err(|_| self.error(format!("cannot parse '{}'", text)))
+    }
+
+    pub fn value<T : FromStr>(&mut self, key : &str) -> Result<T, SnapshotError> {
+        let text = self.keyed_line(key)?;
+        self.parse(text)
+    }
+
+    pub fn list<T : FromStr>(&mut self, key : &str) -> Result<Vec<T>, SnapshotError> {
+        let text = self.keyed_line(key)?;
+        text.split_whitespace().map(|value| self.parse(value)).collect()
+    }
+
+    pub fn grid(&mut self, key : &str) -> Result<Vec<String>, SnapshotError> {
+        let rows_number : usize = self.value(key)?;
+        (0..rows_number).map(|_| self.next_line().map(str::to_string)).collect()
+    }
+
+    fn finish(mut self) -> Result<(), SnapshotError> {
+        match self.lines.find(|line| !line.trim().is_empty()) {
+            Some(line) => Err(self.error(format!("unexpected content after the snapshot: '{}'", line))),
+            None => Ok(()),
+        }
+    }
+}
+
+
+pub fn to_string<S : Snapshot>(state : &S) -> String {
+    let mut writer = SnapshotWriter::new(S::KIND);
+    state.write_snapshot(&mut writer);
+    writer.out_string
+}
+
+pub fn from_str<S : Snapshot>(text : &str) -> Result<S, SnapshotError> {
+    let mut reader = SnapshotReader::new(text, S::KIND)?;
+    let state = S::read_snapshot(&mut reader)?;
+    reader.finish()?;
+    Ok(state)
+}
+
+pub fn save<S : Snapshot>(state : &S, path : &Path) -> std::io::Result<()> {
+    fs::write(path, to_string(state))
+}
+
+pub fn load<S : Snapshot>(path : &Path) -> Result<S, SnapshotError> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| SnapshotError { line : 0, message : format!("cannot read {}: {}", path.display(), error) })?;
+    from_str(&text)
+}
+
+
+// Compares a snapshot with the golden file, or rewrites the file when the
+// UPDATE_GOLDEN environment variable is set.
+pub fn check_golden(golden_path : &Path, actual : &str) -> Result<(), String> {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(golden_path, actual).map_err(|error| error.to_string())?;
+        return Ok(());
+    }
+
+    let expected = fs::read_to_string(golden_path)
+        .map_err(|error| format!("cannot read golden file {}: {}", golden_path.display(), error))?;
+    if expected == actual {
+        return Ok(());
+    }
+
+    let (line_idx, (expected_line, actual_line)) = expected.lines().chain(std::iter::repeat(""))
+        .zip(actual.lines().chain(std::iter::repeat("")))
+        .enumerate()
+        .find(|(_, (expected_line, actual_line))| expected_line != actual_line)
+        .unwrap();
+    Err(format!("{} differs at line {}:\n expected: '{}'\n   actual: '{}'",
+        golden_path.display(), line_idx + 1, expected_line, actual_line))
+}
+
+
+// The snapshot requests given on the command line of a day:
+//   --snapshot <step> <file>   saves the state when the simulation reaches the step
+//   --resume <file>            starts the simulation from a saved state
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotOptions {
+    pub save_at : Option<(u64, PathBuf)>,
+    pub resume_from : Option<PathBuf>,
+}
+
+impl SnapshotOptions {
+    pub fn from_args(args : &mut DayArgs) -> SnapshotOptions {
+        let save_at = args.take_option("--snapshot", 2).map(|values| {
+            let step = values[0].parse::<u64>().unwrap_or_else(|_| panic!("invalid snapshot step '{}'", values[0]));
+            (step, PathBuf::from(&values[1]))
+        });
+        let resume_from = args.take_option("--resume", 1).map(|values| PathBuf::from(&values[0]));
+        SnapshotOptions { save_at, resume_from }
+    }
+
+    // To be called after each step of the simulation.
+    pub fn save_if_at<S : Snapshot>(&self, step : u64, state : &S) {
+        if let Some((save_step, path)) = &self.save_at {
+            if *save_step == step {
+                save(state, path).unwrap_or_else(|error| panic!("cannot write snapshot {}: {}", path.display(), error));
+                println!("Saved snapshot of step {} in {}", step, path.display());
+            }
+        }
+    }
+
+    // Same, for the states that are costly to build: they are made only when saved.
+    pub fn save_if_at_with<S : Snapshot>(&self, step : u64, make_state : impl FnOnce() -> S) {
+        if matches!(&self.save_at, Some((save_step, _)) if *save_step == step) {
+            self.save_if_at(step, &make_state());
+        }
+    }
+
+    pub fn load_resume<S : Snapshot>(&self) -> Option<S> {
+        let path = self.resume_from.as_ref()?;
+        let state = load(path).unwrap_or_else(|error| panic!("cannot resume from {}: {}", path.display(), error));
+        println!("Resuming from snapshot {}", path.display());
+        Some(state)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TestState {
+        step : u32,
+        values : Vec<i32>,
+        map : Vec<String>,
+    }
+
+    impl Snapshot for TestState {
+        const KIND : &'static str = "test_state";
+
+        fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+            writer.value("step", self.step);
+            writer.list("values", &self.values);
+            writer.grid("map", &self.map);
+        }
+
+        fn read_snapshot(reader : &mut SnapshotReader) -> Result<TestState, SnapshotError> {
+            Ok(TestState {
+                step : reader.value("step")?,
+                values : reader.list("values")?,
+                map : reader.grid("map")?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let state = TestState { step : 12, values : vec![3, -1, 4], map : vec!["#..".to_string(), ".o#".to_string()] };
+        let text = to_string(&state);
+        assert_eq!(text, "snapshot test_state\nstep 12\nvalues 3 -1 4\nmap 2\n#..\n.o#\n");
+        assert_eq!(from_str::<TestState>(&text).unwrap(), state);
+
+        let empty = TestState { step : 0, values : vec![], map : vec![] };
+        assert_eq!(from_str::<TestState>(&to_string(&empty)).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(from_str::<TestState>("snapshot other\n").unwrap_err().line, 1);
+        assert_eq!(from_str::<TestState>("snapshot test_state\nstep x\n").unwrap_err().line, 2);
+        assert_eq!(from_str::<TestState>("snapshot test_state\nvalues 1\n").unwrap_err().line, 2);
+        assert!(from_str::<TestState>("snapshot test_state\nstep 1\nvalues\nmap 2\n#\n").is_err());
+        assert!(from_str::<TestState>("snapshot test_state\nstep 1\nvalues\nmap 0\nextra\n").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+// Stepper: an interactive prompt to run the simulations one step at a time
+// and look at their state, mostly for debugging.
+//
+//   step [N]                   one step, or N steps (an empty line is one step)
+//   run <N>                    runs until step N
+//   until <field> <op> <value> runs until the condition holds, op is one of == != < <= > >=
+//   print                      shows the fields of the state
+//   draw                       shows the drawing of the state
+//   quit
+
+use std::io::{self, BufRead, Write};
+
+
+// Implemented by the simulations that can be stepped.
+pub trait Steppable {
+    // Advances by one step, false if the simulation was already over.
+    fn step(&mut self) -> bool;
+
+    fn step_number(&self) -> u64;
+
+    // The values shown by "print", which can be used in the conditions of "until".
+    fn fields(&self) -> Vec<(String, String)>;
+
+    fn draw(&self) -> String {
+        "Nothing to draw.".to_string()
+    }
+}
+
+
+const HELP : &str = "\
+Commands:
+  step [N]                    one step, or N steps (an empty line is one step)
+  run <N>                     runs until step N
+  until <field> <op> <value>  runs until the condition holds, op is one of == != < <= > >=
+  print                       shows the fields of the state
+  draw                        shows the drawing of the state
+  quit";
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    pub field : String,
+    pub operator : String,
+    pub value : String,
+}
+
+impl Condition {
+    pub fn parse(words : &[&str]) -> Result<Condition, String> {
+        let [field, operator, value] = words else {
+            return Err("expected 'until <field> <op> <value>'".to_string());
+        };
+        if !["==", "!=", "<", "<=", ">", ">="].contains(operator) {
+            return Err(format!("unknown operator '{}'", operator));
+        }
+        Ok(Condition { field : field.to_string(), operator : operator.to_string(), value : value.to_string() })
+    }
+
+    // The values are compared as numbers when both are numbers, as text otherwise.
+    pub fn holds(&self, fields : &[(String, String)]) -> Result<bool, String> {
+        let (_, current) = fields.iter().find(|(name, _)| *name == self.field)
+            .ok_or(format!("unknown field '{}'", self.field))?;
+        let ordering = match (current.parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(current_number), Ok(number)) => current_number.partial_cmp(&number),
+            _ => Some(current.as_str().cmp(self.value.as_str())),
+        };
+        let Some(ordering) = ordering else {
+            return Ok(false);
+        };
+        Ok(match self.operator.as_str() {
+            "==" => ordering.is_eq(),
+            "!=" => ordering.is_ne(),
+            "<" => ordering.is_lt(),
+            "<=" => ordering.is_le(),
+            ">" => ordering.is_gt(),
+            _ => ordering.is_ge(),
+        })
+    }
+}
+
+
+fn format_fields(state : &impl Steppable) -> String {
+    let fields : Vec<String> = state.fields().iter().map(|(name, value)| format!("{} = {}", name, value)).collect();
+    format!("step {}: {}", state.step_number(), fields.join(", "))
+}
+
+
+// Steps until the stop function says so, or the simulation is over.
+fn advance<S : Steppable>(state : &mut S, mut should_stop : impl FnMut(&S) -> Result<bool, String>) -> Result<String, String> {
+    loop {
+        if should_stop(state)? {
+            return Ok(format_fields(state));
+        }
+        if !state.step() {
+            return Ok(format!("{} (the simulation is over)", format_fields(state)));
+        }
+    }
+}
+
+
+// Executes one command line, returning the text to show or None to quit.
+pub fn execute_command<S : Steppable>(state : &mut S, line : &str) -> Result<Option<String>, String> {
+    let words : Vec<&str> = line.split_whitespace().collect();
+    let parse_number = |word : Option<&&str>| -> Result<u64, String> {
+        let word = word.ok_or("missing number".to_string())?;
+        word.parse::<u64>().map_err(|_| format!("'{}' is not a number", word))
+    };
+
+    let text = match words.first().copied().unwrap_or("step") {
+        "step" | "s" => {
+            let steps_number = if words.len() > 1 { parse_number(words.get(1))? } else { 1 };
+            let target_step = state.step_number() + steps_number;
+            advance(state, |state| Ok(state.step_number() >= target_step))?
+        },
+        "run" | "r" => {
+            let target_step = parse_number(words.get(1))?;
+            if target_step < state.step_number() {
+                return Err(format!("step {} is already past", target_step));
+            }
+            advance(state, |state| Ok(state.step_number() >= target_step))?
+        },
+        "until" | "u" => {
+            let condition = Condition::parse(&words[1..])?;
+            condition.holds(&state.fields())?;
+            let start_step = state.step_number();
+            advance(state, |state| Ok(state.step_number() > start_step && condition.holds(&state.fields())?))?
+        },
+        "print" | "p" => format_fields(state),
+        "draw" | "d" => state.draw(),
+        "help" | "h" => HELP.to_string(),
+        "quit" | "q" => return Ok(None),
+        command => return Err(format!("unknown command '{}', 'help' for the list", command)),
+    };
+    Ok(Some(text))
+}
+
+
+// The prompt loop, until "quit" or the end of the input.
+pub fn run_stepper<S : Steppable>(state : &mut S, input : impl BufRead, output : &mut impl Write) -> io::Result<()> {
+    writeln!(output, "{}\n{}", HELP, format_fields(state))?;
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        match execute_command(state, &line?) {
+            Ok(Some(text)) => writeln!(output, "{}", text)?,
+            Ok(None) => return Ok(()),
+            Err(message) => writeln!(output, "error: {}", message)?,
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts up to a limit, the square is there to test the conditions.
+    struct Counter {
+        value : u64,
+        limit : u64,
+    }
+
+    impl Steppable for Counter {
+        fn step(&mut self) -> bool {
+            if self.value >= self.limit {
+                return false;
+            }
+            self.value += 1;
+            true
+        }
+
+        fn step_number(&self) -> u64 {
+            self.value
+        }
+
+        fn fields(&self) -> Vec<(String, String)> {
+            vec![("square".to_string(), (self.value * self.value).to_string()),
+                 ("parity".to_string(), if self.value.is_multiple_of(2) {"even"} else {"odd"}.to_string())]
+        }
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut counter = Counter { value : 0, limit : 50 };
+        assert_eq!(execute_command(&mut counter, "").unwrap().unwrap(), "step 1: square = 1, parity = odd");
+        execute_command(&mut counter, "step 4").unwrap();
+        assert_eq!(counter.value, 5);
+        execute_command(&mut counter, "run 12").unwrap();
+        assert_eq!(counter.value, 12);
+        assert!(execute_command(&mut counter, "run 3").is_err());
+
+        execute_command(&mut counter, "until square > 200").unwrap();
+        assert_eq!(counter.value, 15);
+        execute_command(&mut counter, "until parity == odd").unwrap();
+        assert_eq!(counter.value, 17);
+        assert!(execute_command(&mut counter, "until colour == red").is_err());
+        assert!(execute_command(&mut counter, "until square ~ 3").is_err());
+
+        let text = execute_command(&mut counter, "until square < 0").unwrap().unwrap();
+        assert!(text.ends_with("(the simulation is over)"));
+        assert_eq!(counter.value, 50);
+
+        assert_eq!(execute_command(&mut counter, "quit").unwrap(), None);
+        assert!(execute_command(&mut counter, "jump").is_err());
+    }
+
+    #[test]
+    fn test_run_stepper() {
+        let mut counter = Counter { value : 0, limit : 50 };
+        let mut output = Vec::<u8>::new();
+        run_stepper(&mut counter, "step 3\nprint\nbogus\nquit\nstep\n".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("> step 3: square = 9, parity = odd\n"));
+        assert!(output.contains("error: unknown command 'bogus'"));
+        assert_eq!(counter.value, 3);
+    }
+}
@@ -0,0 +1,79 @@
+// Top K: keeping the K greatest values of a stream, without storing the stream.
+//
+// The values are kept in a min-heap of at most K elements: a new value only
+// enters by replacing the smallest one kept, so the memory is O(K) and each
+// value costs O(log K).
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+
+pub struct TopK<T : Ord> {
+    capacity : usize,
+    heap : BinaryHeap<Reverse<T>>,
+}
+
+impl<T : Ord> TopK<T> {
+    pub fn new(capacity : usize) -> TopK<T> {
+        TopK { capacity, heap : BinaryHeap::with_capacity(capacity + 1) }
+    }
+
+    pub fn push(&mut self, value : T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.heap.len() < self.capacity {
+            self.heap.push(Reverse(value));
+        }
+        else if self.heap.peek().is_some_and(|Reverse(smallest)| value > *smallest) {
+            self.heap.pop();
+            self.heap.push(Reverse(value));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    // The values kept, greatest first.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap.into_sorted_vec().into_iter().map(|Reverse(value)| value).collect()
+    }
+}
+
+
+// The K greatest values of the iterator, greatest first.
+pub fn top_k<T : Ord>(values : impl IntoIterator<Item = T>, capacity : usize) -> Vec<T> {
+    let mut top_values = TopK::new(capacity);
+    for value in values {
+        top_values.push(value);
+    }
+    top_values.into_sorted_vec()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k() {
+        let values = [5, 1, 9, 3, 9, 7, 2];
+        assert_eq!(top_k(values, 3), vec![9, 9, 7]);
+        assert_eq!(top_k(values, 1), vec![9]);
+        assert_eq!(top_k(values, 10), vec![9, 9, 7, 5, 3, 2, 1]);
+        assert!(top_k(values, 0).is_empty());
+        assert!(top_k(Vec::<i32>::new(), 3).is_empty());
+
+        let mut top_values = TopK::new(2);
+        for value in (0..100000).map(|value| (value * 7919) % 100003) {
+            top_values.push(value);
+            assert!(top_values.len() <= 2);
+        }
+        assert_eq!(top_values.into_sorted_vec(), vec![100002, 100001]);
+    }
+}
@@ -1,43 +1,461 @@
-// Exercise 1: parse a file containing empty-line-separated sets of values, adding them together and finding the highest. 
+// Exercise 1: parse a file containing empty-line-separated sets of values, adding them together and finding the highest.
 
 // For reading/parsing
-use std::fs::File;
-use std::io::{self, prelude::*, BufReader};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
 
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 1!");
+// Utility
+use common::cli::DayArgs;
+use common::groups;
+use common::memory;
+use common::top_k::TopK;
+
+// A group can start with a header naming the elf, "Elf Alice:".
+// Returns the name, if any, and the calories of the items, one per line.
+fn parse_group(group : &[String]) -> (Option<String>, Vec<i32>) {
+    let (label, item_lines) = match group.first().map(|line| line.trim()) {
+        Some(first_line) if first_line.ends_with(':') => (Some(first_line.trim_end_matches(':').trim().to_string()), &group[1..]),
+        _ => (None, group),
+    };
+    (label, item_lines.iter().map(|line| line.trim().parse::<i32>().unwrap()).collect())
+}
+
+
+// The calories carried by one elf.
+fn group_sum(group : &[String]) -> i32 {
+    parse_group(group).1.iter().sum()
+}
+
+
+// The labels of the elves (the unnamed ones are numbered from 1) and their items.
+fn read_items_lists(reader : impl BufRead) -> (Vec<String>, Vec<Vec<i32>>) {
+    let mut labels = Vec::<String>::new();
+    let mut items_lists = Vec::<Vec<i32>>::new();
+    for group in groups::groups(reader) {
+        let (label, items) = parse_group(&group.unwrap());
+        labels.push(label.unwrap_or(format!("Elf {}", labels.len() + 1)));
+        items_lists.push(items);
+    }
+    (labels, items_lists)
+}
+
+
+// The groups of the inventory: the labels, the number of items and the total of each elf.
+fn read_inventory(reader : impl BufRead) -> (Vec<String>, Vec<usize>, Vec<i32>) {
+    let (labels, items_lists) = read_items_lists(reader);
+    let items_counts = items_lists.iter().map(|items| items.len()).collect();
+    let cumulated_values_vec = items_lists.iter().map(|items| items.iter().sum()).collect();
+    (labels, items_counts, cumulated_values_vec)
+}
+
+
+// Statistics on the totals of the elves.
+struct InventoryReport {
+    labels : Vec<String>,
+    items_counts : Vec<usize>,
+    cumulated_values_vec : Vec<i32>,
+    ranks : Vec<usize>,    // 1 for the greatest total, the ties share the rank ("1224").
+    mean : f64,
+    median : f64,
+    percentiles : Vec<(u32, i32)>,
+}
+
+const REPORT_PERCENTILES : [u32; 5] = [10, 25, 50, 75, 90];
+
+impl InventoryReport {
+    fn new(labels : Vec<String>, items_counts : Vec<usize>, cumulated_values_vec : Vec<i32>) -> Option<InventoryReport> {
+        if cumulated_values_vec.is_empty() {
+            return None;
+        }
+        let mut sorted_values = cumulated_values_vec.clone();
+        sorted_values.sort();
+        let elves_number = sorted_values.len();
+
+        // The rank is one more than the number of elves carrying strictly more.
+        let ranks = cumulated_values_vec.iter()
+            .map(|value| elves_number - sorted_values.partition_point(|other| other <= value) + 1)
+            .collect();
+        let mean = sorted_values.iter().map(|&value| value as f64).sum::<f64>() / elves_number as f64;
+        let median = match elves_number % 2 {
+            0 => (sorted_values[elves_number / 2 - 1] as f64 + sorted_values[elves_number / 2] as f64) / 2.0,
+            _ => sorted_values[elves_number / 2] as f64,
+        };
+        let percentiles = REPORT_PERCENTILES.iter().map(|&percent| (percent, percentile(&sorted_values, percent))).collect();
+
+        Some(InventoryReport { labels, items_counts, cumulated_values_vec, ranks, mean, median, percentiles })
+    }
+
+    // The elves from the first rank to the last, in the order of the file within a tie.
+    fn ranking(&self) -> Vec<usize> {
+        let mut order : Vec<usize> = (0..self.cumulated_values_vec.len()).collect();
+        order.sort_by_key(|&idx| self.ranks[idx]);
+        order
+    }
+
+    fn draw(&self) -> String {
+        let label_width = self.labels.iter().map(|label| label.chars().count()).max().unwrap_or(0).max(3);
+        let mut out_string = format!("{:>4}  {:<label_width$}  {:>5}  {:>8}\n", "rank", "elf", "items", "total");
+        for idx in self.ranking() {
+            out_string += &format!("{:>4}  {:<label_width$}  {:>5}  {:>8}\n",
+                self.ranks[idx], self.labels[idx], self.items_counts[idx], self.cumulated_values_vec[idx]);
+        }
+        out_string += &format!("{} elves, mean {:.1}, median {:.1}\n", self.cumulated_values_vec.len(), self.mean, self.median);
+        let percentiles : Vec<String> = self.percentiles.iter().map(|(percent, value)| format!("p{} {}", percent, value)).collect();
+        out_string += &format!("percentiles: {}\n", percentiles.join(", "));
+        out_string
+    }
+
+    // One line per elf in the ranking order, the labels quoted when needed.
+    fn to_csv(&self) -> String {
+        let mut out_string = "rank,elf,items,total\n".to_string();
+        for idx in self.ranking() {
+            out_string += &format!("{},{},{},{}\n",
+                self.ranks[idx], csv_field(&self.labels[idx]), self.items_counts[idx], self.cumulated_values_vec[idx]);
+        }
+        out_string
+    }
+}
+
+
+// Nearest-rank percentile: the smallest value with at least the given percentage of the values at or below it.
+fn percentile(sorted_values : &[i32], percent : u32) -> i32 {
+    let rank = (percent as usize * sorted_values.len()).div_ceil(100).max(1);
+    sorted_values[rank - 1]
+}
+
+
+fn csv_field(text : &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    }
+    else {
+        text.to_string()
+    }
+}
+
+
+// Rebalancing: moving snacks between the elves so that nobody carries more than a limit.
+#[derive(Clone, Debug, PartialEq)]
+struct Transfer {
+    calories : i32,
+    from : usize,
+    to : usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum RebalanceError {
+    ItemTooLarge { elf : usize, calories : i32 },
+    NotEnoughRoom { total : i64, capacity : i64 },
+    Impossible,  // The exact search tried everything.
+    NoPlanFound, // The heuristic gave up, a plan may still exist.
+}
+
+impl RebalanceError {
+    fn describe(&self, labels : &[String]) -> String {
+        match self {
+            RebalanceError::ItemTooLarge { elf, calories } =>
+                format!("{} carries a snack of {} calories, over the limit by itself", labels[*elf], calories),
+            RebalanceError::NotEnoughRoom { total, capacity } =>
+                format!("the elves carry {} calories, more than the {} they can carry together", total, capacity),
+            RebalanceError::Impossible => "no distribution of the snacks respects the limit".to_string(),
+            RebalanceError::NoPlanFound => "the heuristic found no plan, the input is too large for the exact search".to_string(),
+        }
+    }
+}
+
+// Up to this number of snacks, all the subsets of moved snacks are tried.
+const EXACT_SEARCH_MAX_ITEMS : usize = 16;
+
+
+// Plans the fewest transfers of single snacks so that every elf carries at most the limit.
+fn plan_rebalance(items_lists : &[Vec<i32>], limit : i32) -> Result<Vec<Transfer>, RebalanceError> {
+    for (elf, items) in items_lists.iter().enumerate() {
+        if let Some(&calories) = items.iter().find(|&&calories| calories > limit) {
+            return Err(RebalanceError::ItemTooLarge { elf, calories });
+        }
+    }
+    let total = items_lists.iter().flatten().map(|&calories| calories as i64).sum::<i64>();
+    let capacity = limit as i64 * items_lists.len() as i64;
+    if total > capacity {
+        return Err(RebalanceError::NotEnoughRoom { total, capacity });
+    }
+
+    if items_lists.iter().map(|items| items.len()).sum::<usize>() <= EXACT_SEARCH_MAX_ITEMS {
+        plan_rebalance_exact(items_lists, limit)
+    }
+    else {
+        plan_rebalance_greedy(items_lists, limit)
+    }
+}
+
+
+// Tries the subsets of snacks to move by increasing size, the first one whose
+// snacks fit in the room left by the others is the answer.
+fn plan_rebalance_exact(items_lists : &[Vec<i32>], limit : i32) -> Result<Vec<Transfer>, RebalanceError> {
+    let items : Vec<(usize, i32)> = items_lists.iter().enumerate()
+        .flat_map(|(elf, elf_items)| elf_items.iter().map(move |&calories| (elf, calories)))
+        .collect();
+    let loads : Vec<i32> = items_lists.iter().map(|elf_items| elf_items.iter().sum()).collect();
 
-    // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
-    let reader = BufReader::new(file);
-
-    // Cumulated vec (one element per each empty-line separator) and temp value
-    let mut cumulated_values_vec = Vec::<i32>::new();
-    let mut cumulated_value = 0;
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-            if line.is_empty(){
-                // Adding the output in the vector and resetting the support variable.
-                cumulated_values_vec.push(cumulated_value);
-                cumulated_value = 0;
+    for moved_number in 0..=items.len() as u32 {
+        for moved_mask in 0..1u32 << items.len() {
+            if moved_mask.count_ones() != moved_number {
+                continue;
             }
-            else{
-                cumulated_value += line.parse::<i32>().unwrap();
+            let mut free_room : Vec<i32> = loads.iter().map(|load| limit - load).collect();
+            let mut moved_items = Vec::<(usize, i32)>::new();
+            for (item_idx, &(elf, calories)) in items.iter().enumerate() {
+                if moved_mask & (1 << item_idx) != 0 {
+                    free_room[elf] += calories;
+                    moved_items.push((elf, calories));
+                }
             }
+            if free_room.iter().any(|&room| room < 0) {
+                continue;
+            }
+
+            // The largest snacks first, they have the fewest places to go.
+            moved_items.sort_by_key(|&(_, calories)| -calories);
+            let mut destinations = Vec::<usize>::new();
+            if place_items(&moved_items, &mut free_room, &mut destinations) {
+                return Ok(moved_items.iter().zip(destinations)
+                    .map(|(&(from, calories), to)| Transfer { calories, from, to })
+                    .collect());
+            }
+        }
+    }
+    Err(RebalanceError::Impossible)
+}
+
+
+// Backtracking over the elves receiving each snack (never the one it comes from).
+fn place_items(items : &[(usize, i32)], free_room : &mut [i32], destinations : &mut Vec<usize>) -> bool {
+    let Some(&(from, calories)) = items.get(destinations.len()) else {
+        return true;
+    };
+    for to in 0..free_room.len() {
+        // Two elves with the same room are the same choice.
+        if to == from || free_room[to] < calories || (0..to).any(|other| other != from && free_room[other] == free_room[to]) {
+            continue;
         }
+        free_room[to] -= calories;
+        destinations.push(to);
+        if place_items(items, free_room, destinations) {
+            return true;
+        }
+        destinations.pop();
+        free_room[to] += calories;
+    }
+    false
+}
+
+
+// For the large inputs: each overloaded elf gives away the smallest snack that
+// is enough to get under the limit, or its largest one if none is. Then the
+// snacks go, largest first, to the elf with the least room where they fit.
+fn plan_rebalance_greedy(items_lists : &[Vec<i32>], limit : i32) -> Result<Vec<Transfer>, RebalanceError> {
+    let mut free_room : Vec<i32> = items_lists.iter().map(|items| limit - items.iter().sum::<i32>()).collect();
+    let mut moved_items = Vec::<(usize, i32)>::new();
+    for (elf, items) in items_lists.iter().enumerate() {
+        let mut kept_items = items.clone();
+        kept_items.sort();
+        while free_room[elf] < 0 {
+            let excess = -free_room[elf];
+            let item_idx = kept_items.iter().position(|&calories| calories >= excess).unwrap_or(kept_items.len() - 1);
+            let calories = kept_items.remove(item_idx);
+            free_room[elf] += calories;
+            moved_items.push((elf, calories));
+        }
+    }
+
+    moved_items.sort_by_key(|&(_, calories)| -calories);
+    let mut transfers = Vec::<Transfer>::new();
+    for (from, calories) in moved_items {
+        let to = (0..free_room.len())
+            .filter(|&to| to != from && free_room[to] >= calories)
+            .min_by_key(|&to| free_room[to])
+            .ok_or(RebalanceError::NoPlanFound)?;
+        free_room[to] -= calories;
+        transfers.push(Transfer { calories, from, to });
+    }
+    Ok(transfers)
+}
+
+
+fn apply_transfers(items_lists : &[Vec<i32>], transfers : &[Transfer]) -> Vec<Vec<i32>> {
+    let mut items_lists = items_lists.to_vec();
+    for transfer in transfers {
+        let item_idx = items_lists[transfer.from].iter().position(|&calories| calories == transfer.calories).unwrap();
+        items_lists[transfer.from].remove(item_idx);
+        items_lists[transfer.to].push(transfer.calories);
     }
+    items_lists
+}
+
+
+// The K elves carrying the most, greatest first. The file is streamed and only
+// K totals are kept, whatever the size of the inventory.
+fn top_elves(input_path : &str, elves_number : usize) -> Vec<i32> {
+    let reader = BufReader::new(File::open(input_path).unwrap());
+    let mut top_values = TopK::new(elves_number);
+    for group in groups::groups(reader) {
+        top_values.push(group_sum(&group.unwrap()));
+    }
+    top_values.into_sorted_vec()
+}
+
+
+// Primary Function
+fn execute (input_path : String)  -> Option<(i32, i32)> {
+
+    // Streaming the group totals through a single top 3, nothing else is kept around
+    // (the last group doesn't need an empty line after it).
+    let top_three = top_elves(&input_path, 3);
+
+    // Returning the result of PART 1: the greatest is the first of the top 3.
+    let result_part_1 = *top_three.first()?;
+    memory::report_part(1);
+
+    // PART 2 - sum the three greatest.
+    let result_part_2 = top_three.into_iter().sum::<i32>();
 
-    // Returning the result of PART 1.
-    println!("Greatest value among {} is {}.", cumulated_values_vec.len(), cumulated_values_vec.iter().max().unwrap());
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
 
-    // PART 2 - find the three greatest and sum them.
+// Main
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 1!");
 
-    // Sorting the vector: 
-    cumulated_values_vec.sort();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // With --top <K> the K elves carrying the most are listed too, with --report the
+    // statistics of all the elves, which --csv <file> saves. With --rebalance <limit> the
+    // snacks are moved so that nobody carries more than the limit.
+    let mut args = DayArgs::from_env();
+    let top_option = args.take_option("--top", 1);
+    let report_mode = args.take_switch("--report");
+    let csv_option = args.take_option("--csv", 1);
+    let rebalance_option = args.take_option("--rebalance", 1);
+    let input_path = args.input_path("./data/input.txt");
+    if let Some(values) = rebalance_option {
+        let limit = values[0].parse::<i32>().expect("--rebalance expects a number of calories");
+        let (labels, items_lists) = read_items_lists(BufReader::new(File::open(&input_path)?));
+        match plan_rebalance(&items_lists, limit) {
+            Ok(transfers) => {
+                for transfer in &transfers {
+                    println!("Move a snack of {} calories from {} to {}.", transfer.calories, labels[transfer.from], labels[transfer.to]);
+                }
+                let max_load = apply_transfers(&items_lists, &transfers).iter().map(|items| items.iter().sum::<i32>()).max().unwrap_or(0);
+                println!("{} snacks moved, the most carried is now {}.", transfers.len(), max_load);
+            },
+            Err(error) => println!("Cannot rebalance under {}: {}.", limit, error.describe(&labels)),
+        }
+    }
+    if report_mode || csv_option.is_some() {
+        let (labels, items_counts, cumulated_values_vec) = read_inventory(BufReader::new(File::open(&input_path)?));
+        let report = InventoryReport::new(labels, items_counts, cumulated_values_vec).expect("the inventory is empty");
+        if report_mode {
+            print!("{}", report.draw());
+        }
+        if let Some(values) = csv_option {
+            fs::write(&values[0], report.to_csv())?;
+            println!("Saved the report in {}.", values[0]);
+        }
+    }
+    if let Some(values) = top_option {
+        let elves_number = values[0].parse::<usize>().expect("--top expects a number of elves");
+        let top_values = top_elves(&input_path, elves_number);
+        println!("Top {} elves carry {:?}, {} in total.", elves_number, top_values, top_values.iter().sum::<i32>());
+    }
+    let results = execute(input_path).unwrap();
 
-    // Retrieving the last and the last three combined:
-    println!("Three highest values combined are {}.", cumulated_values_vec.iter().rev().take(3).sum::<i32>());
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // General Test
+    #[test]
+    fn global_test() {
+        assert_eq!(execute("./data/test.txt".to_string()), Some((24000, 45000)));
+    }
+
+    // The last elf of the example carries the most and has no empty line after it.
+    #[test]
+    fn test_last_group_counted() {
+        assert_eq!(execute("./data/test_no_trailing_line.txt".to_string()), Some((30000, 65000)));
+        assert_eq!(top_elves("./data/test_no_trailing_line.txt", 2), vec![30000, 24000]);
+        assert_eq!(top_elves("./data/test.txt", 100), vec![24000, 11000, 10000, 6000, 4000]);
+    }
+
+    // Labelled inventories and report
+    #[test]
+    fn test_labelled_inventory() {
+        let (labels, items_counts, cumulated_values_vec) = read_inventory(BufReader::new(File::open("./data/test_labelled.txt").unwrap()));
+        assert_eq!(labels, vec!["Elf Alice", "Elf 2", "Elf Bob, Jr.", "Elf Carol", "Elf 5"]);
+        assert_eq!(items_counts, vec![3, 1, 2, 1, 3]);
+        assert_eq!(cumulated_values_vec, vec![6000, 4000, 11000, 6000, 24000]);
+        assert_eq!(execute("./data/test_labelled.txt".to_string()), Some((24000, 41000)));
+        assert_eq!(top_elves("./data/test_labelled.txt", 2), vec![24000, 11000]);
+
+        let report = InventoryReport::new(labels, items_counts, cumulated_values_vec).unwrap();
+        assert_eq!(report.ranks, vec![3, 5, 2, 3, 1]);
+        assert_eq!(report.mean, 10200.0);
+        assert_eq!(report.median, 6000.0);
+        assert_eq!(report.percentiles, vec![(10, 4000), (25, 6000), (50, 6000), (75, 11000), (90, 24000)]);
+        assert_eq!(report.to_csv(), "rank,elf,items,total\n1,Elf 5,3,24000\n2,\"Elf Bob, Jr.\",2,11000\n\
+            3,Elf Alice,3,6000\n3,Elf Carol,1,6000\n5,Elf 2,1,4000\n");
+        assert!(report.draw().contains("5 elves, mean 10200.0, median 6000.0"));
+    }
+
+    #[test]
+    fn test_report_statistics() {
+        let report = InventoryReport::new(vec!["a".to_string(), "b".to_string()], vec![1, 1], vec![3, 8]).unwrap();
+        assert_eq!((report.mean, report.median), (5.5, 5.5));
+        assert_eq!(percentile(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 90), 9);
+        assert_eq!(percentile(&[7], 10), 7);
+        assert!(InventoryReport::new(Vec::new(), Vec::new(), Vec::new()).is_none());
+    }
+
+    // Rebalancing
+    fn max_load(items_lists : &[Vec<i32>]) -> i32 {
+        items_lists.iter().map(|items| items.iter().sum::<i32>()).max().unwrap()
+    }
+
+    #[test]
+    fn test_rebalance_exact() {
+        // Only the 4 can leave the first elf: the 7 fits nowhere.
+        let items_lists = vec![vec![7, 4], vec![5, 1], vec![6]];
+        let transfers = plan_rebalance(&items_lists, 10).unwrap();
+        assert_eq!(transfers, vec![Transfer { calories : 4, from : 0, to : 1 }]);
+
+        // Making room in the second elf for the 6 of the first.
+        let items_lists = vec![vec![6, 6], vec![5, 5], vec![3, 2, 1]];
+        let transfers = plan_rebalance(&items_lists, 10).unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert!(max_load(&apply_transfers(&items_lists, &transfers)) <= 10);
+
+        assert!(plan_rebalance(&items_lists, 12).unwrap().is_empty());
+        assert_eq!(plan_rebalance(&[vec![6, 6], vec![6]], 10), Err(RebalanceError::Impossible));
+        assert_eq!(plan_rebalance(&[vec![6, 6], vec![1]], 5), Err(RebalanceError::ItemTooLarge { elf : 0, calories : 6 }));
+        assert_eq!(plan_rebalance(&[vec![6, 6], vec![1]], 6), Err(RebalanceError::NotEnoughRoom { total : 13, capacity : 12 }));
+    }
+
+    #[test]
+    fn test_rebalance_greedy() {
+        let items_lists : Vec<Vec<i32>> = (0..40).map(|elf| (0..elf % 5).map(|item| 1000 + 700 * ((elf * 7 + item * 3) % 11)).collect()).collect();
+        let transfers = plan_rebalance(&items_lists, 12000).unwrap();
+        assert!(max_load(&items_lists) > 12000);
+        assert!(max_load(&apply_transfers(&items_lists, &transfers)) <= 12000);
+
+        assert_eq!(plan_rebalance_greedy(&[vec![6, 6], vec![6]], 10), Err(RebalanceError::NoPlanFound));
+        assert_eq!(plan_rebalance_greedy(&[vec![7, 4], vec![5, 1], vec![6]], 10).unwrap().len(), 1);
+    }
+}
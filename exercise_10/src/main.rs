@@ -4,6 +4,11 @@
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
 
+//Utility
+use common::cli::DayArgs;
+use common::memory;
+use common::stepper::{self, Steppable};
+
 struct RegisterCounter {
     cycle_counter : u32,
     cumulate_value : i32,
@@ -37,17 +42,13 @@ fn check_pixel(sprite_position : &i32, pixel_index : &u32) -> char {
     '.'
 }
 
-// Primary Function
-fn execute (input_path : String)  -> Option<(u32, String)> {
+// Reading the commands, with their value for "addx".
+fn read_commands(input_path : &str) -> Vec<(String, Option<i32>)> {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
-    // Results variables:
-    let result_part_1;
-    let mut result_part_2;
-
     // First reading the input string - easy.
     let mut lines_vec = Vec::<(String, Option<i32>)>::new();
     // Finally reading the stuff.
@@ -58,6 +59,89 @@ fn execute (input_path : String)  -> Option<(u32, String)> {
                 line.split(" ").nth(1).unwrap_or("").parse::<i32>().ok()));
         }
     }
+    lines_vec
+}
+
+
+// Both parts at once, one clock cycle per step, for the stepper.
+struct CpuStepper {
+    lines_vec : Vec<(String, Option<i32>)>,
+    next_line : usize,
+    addx_started : bool, // The first cycle of the current "addx" is done.
+    reg_value : i32,
+    register_counter : RegisterCounter,
+    out_screen : Vec<char>,
+}
+
+impl CpuStepper {
+    fn new(lines_vec : Vec<(String, Option<i32>)>) -> CpuStepper {
+        CpuStepper {
+            lines_vec,
+            next_line : 0,
+            addx_started : false,
+            reg_value : 1,
+            register_counter : RegisterCounter::new(),
+            out_screen : Vec::new(),
+        }
+    }
+}
+
+impl Steppable for CpuStepper {
+    fn step(&mut self) -> bool {
+        let Some(line) = self.lines_vec.get(self.next_line) else {
+            return false;
+        };
+
+        // During the cycle the pixel is drawn and the signal cumulated, the register changes at the end.
+        self.out_screen.push(check_pixel(&self.reg_value, &(self.register_counter.cycle_counter + 1)));
+        self.register_counter.cumulate_step_40(&self.reg_value);
+        match line.0.as_str() {
+            "addx" if !self.addx_started => self.addx_started = true,
+            "addx" => {
+                self.reg_value += line.1.unwrap();
+                self.addx_started = false;
+                self.next_line += 1;
+            },
+            "noop" => self.next_line += 1,
+            _ => panic!("wrong line command!"),
+        }
+        true
+    }
+
+    fn step_number(&self) -> u64 {
+        self.register_counter.cycle_counter as u64
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        let (command, argument) = match self.lines_vec.get(self.next_line) {
+            Some((command, argument)) => (command.clone(), argument.map(|value| value.to_string()).unwrap_or("-".to_string())),
+            None => ("none".to_string(), "-".to_string()),
+        };
+        vec![
+            ("cycle_counter".to_string(), self.register_counter.cycle_counter.to_string()),
+            ("cumulate_value".to_string(), self.register_counter.cumulate_value.to_string()),
+            ("register".to_string(), self.reg_value.to_string()),
+            ("command".to_string(), command),
+            ("argument".to_string(), argument),
+        ]
+    }
+
+    // The screen so far.
+    fn draw(&self) -> String {
+        let rows : Vec<String> = self.out_screen.chunks(40).map(String::from_iter).collect();
+        rows.join("\n")
+    }
+}
+
+
+// Primary Function
+fn execute (input_path : String)  -> Option<(u32, String)> {
+
+    // Results variables:
+    let result_part_1;
+    let mut result_part_2;
+
+    let lines_vec = read_commands(&input_path);
     println!("read {} lines from input", lines_vec.len());
     assert!(lines_vec.len() > 1);
 
@@ -78,6 +162,7 @@ fn execute (input_path : String)  -> Option<(u32, String)> {
     }
     println!("After the commands the value is {}", register_counter.cumulate_value);
     result_part_1 = register_counter.cumulate_value.try_into().unwrap();
+    memory::report_part(1);
 
     // For Part 2, things are a bit more complicated. The commands above now move a 3 pixels wide sprite
     // and each cycle it checks the position of the sprite compared to the position of a pixel.
@@ -113,6 +198,7 @@ fn execute (input_path : String)  -> Option<(u32, String)> {
         result_part_2 += &(format!("{}\n", substring));
     }
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -120,7 +206,17 @@ fn execute (input_path : String)  -> Option<(u32, String)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 10!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // With --step the cycles can be run interactively instead.
+    let mut args = DayArgs::from_env();
+    let step_mode = args.take_switch("--step");
+    let input_path = args.input_path("./data/input.txt");
+    if step_mode {
+        let mut cpu_stepper = CpuStepper::new(read_commands(&input_path));
+        return stepper::run_stepper(&mut cpu_stepper, io::stdin().lock(), &mut io::stdout());
+    }
+
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -140,6 +236,21 @@ mod tests {
         assert_eq!(execute("./data/test.txt".to_string()).unwrap().0, 13140);
     }    
 
+    #[test]
+    fn test_cpu_stepper() {
+        let mut cpu_stepper = CpuStepper::new(read_commands("./data/test.txt"));
+        stepper::execute_command(&mut cpu_stepper, "run 20").unwrap();
+        assert_eq!((cpu_stepper.register_counter.cumulate_value, cpu_stepper.reg_value), (420, 21));
+
+        stepper::execute_command(&mut cpu_stepper, "until cumulate_value > 1000").unwrap();
+        assert_eq!(cpu_stepper.step_number(), 60);
+
+        stepper::execute_command(&mut cpu_stepper, "run 1000").unwrap();
+        assert_eq!(cpu_stepper.step_number(), 240);
+        assert_eq!(cpu_stepper.register_counter.cumulate_value, 13140);
+        assert!(cpu_stepper.draw().starts_with("##..##..##..##..##..##..##..##..##..##..\n"));
+    }
+
     // Skipping the second test since it's just visual
     // #[test]
     // fn global_test_part_2() {
@@ -8,6 +8,14 @@ use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
 
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use common::cli::DayArgs;
+use common::memory;
+use common::snapshot::{Snapshot, SnapshotError, SnapshotOptions, SnapshotReader, SnapshotWriter};
+
+// The primes tracked by the rotating counter, which include all the divisors of the input.
+const PRIMES : [i32; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
 
 // parsing the info about the monkey
 struct Monkey {
@@ -28,17 +36,7 @@ impl RotatingCounter {
 
     fn new(input : i32) -> RotatingCounter {
         RotatingCounter {
-            counters: HashMap::from([
-                (2 , input % 2 ),
-                (3 , input % 3 ),
-                (5 , input % 5 ),
-                (7 , input % 7 ),
-                (11, input % 11),
-                (13, input % 13),
-                (17, input % 17),
-                (19, input % 19),
-                (23, input % 23)
-            ])
+            counters: PRIMES.iter().map(|&prime| (prime, input % prime)).collect()
         }
     }
 
@@ -64,6 +62,82 @@ impl RotatingCounter {
 }
 
 
+// Written as the residues of the primes in increasing order, "1,2,0,..."
+impl fmt::Display for RotatingCounter {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        let residues : Vec<String> = PRIMES.iter().map(|prime| self.counters[prime].to_string()).collect();
+        write!(f, "{}", residues.join(","))
+    }
+}
+
+impl FromStr for RotatingCounter {
+    type Err = String;
+
+    fn from_str(text : &str) -> Result<RotatingCounter, String> {
+        let residues : Vec<i32> = text.split(',')
+            .map(|residue| residue.parse::<i32>().map_err(|_| format!("invalid residue '{}'", residue)))
+            .collect::<Result<_, _>>()?;
+        if residues.len() != PRIMES.len() || residues.iter().zip(PRIMES).any(|(&residue, prime)| residue < 0 || residue >= prime) {
+            return Err(format!("expected {} residues of the primes, got '{}'", PRIMES.len(), text));
+        }
+        Ok(RotatingCounter { counters : PRIMES.into_iter().zip(residues).collect() })
+    }
+}
+
+
+// The part of the monkeys that changes during the rounds: the operations and
+// the throwing rules come from the input and are not saved.
+struct MonkeysState {
+    round : u32,
+    inspect_counters : Vec<i32>,
+    items : Vec<Vec<RotatingCounter>>,
+}
+impl MonkeysState {
+    fn from_monkeys(round : u32, monkeys_vec : &[Monkey]) -> MonkeysState {
+        MonkeysState {
+            round,
+            inspect_counters : monkeys_vec.iter().map(|monkey| monkey.inspect_counter).collect(),
+            items : monkeys_vec.iter().map(|monkey| monkey.items_vec.clone()).collect(),
+        }
+    }
+
+    fn apply_to(self, monkeys_vec : &mut [Monkey]) {
+        assert!(self.items.len() == monkeys_vec.len(), "The snapshot has {} monkeys, the input {}", self.items.len(), monkeys_vec.len());
+        for ((monkey, inspect_counter), items_vec) in monkeys_vec.iter_mut().zip(self.inspect_counters).zip(self.items) {
+            monkey.inspect_counter = inspect_counter;
+            monkey.items_vec = items_vec;
+        }
+    }
+}
+
+impl Snapshot for MonkeysState {
+    const KIND : &'static str = "monkeys";
+
+    fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+        writer.value("round", self.round);
+        writer.value("monkeys", self.items.len());
+        writer.list("inspect_counters", &self.inspect_counters);
+        for (idx, items_vec) in self.items.iter().enumerate() {
+            writer.list(&format!("items_{}", idx), items_vec);
+        }
+    }
+
+    fn read_snapshot(reader : &mut SnapshotReader) -> Result<MonkeysState, SnapshotError> {
+        let round = reader.value("round")?;
+        let monkeys_number : usize = reader.value("monkeys")?;
+        let inspect_counters : Vec<i32> = reader.list("inspect_counters")?;
+        if inspect_counters.len() != monkeys_number {
+            return Err(reader.error(format!("expected {} inspect counters", monkeys_number)));
+        }
+        let mut items = Vec::<Vec<RotatingCounter>>::new();
+        for idx in 0..monkeys_number {
+            items.push(reader.list(&format!("items_{}", idx))?);
+        }
+        Ok(MonkeysState { round, inspect_counters, items })
+    }
+}
+
+
 fn cleanup_monkey_input (mut input_lines : Vec<String>) -> Vec<String> {
     // Cleaning up the info
     assert!(input_lines.len() == 6);
@@ -143,7 +217,7 @@ fn parse_monkey(input_lines : Vec<String>) -> Monkey {
 
 
 // Primary Function
-fn execute (input_path : String)  -> Option<(u64, u64)> {
+fn execute (input_path : String, snapshots : &SnapshotOptions)  -> Option<(u64, u64)> {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
@@ -227,7 +301,13 @@ fn execute (input_path : String)  -> Option<(u64, u64)> {
     // For part 2 it's all the same, only without the /3 and iterating 10.000 times.
     // This however poses a problem - numbers become huge, so i should track the prime
     // numbers involved!
-    for _ in 0..10000 {
+    // The round can be restored from a snapshot, the step of the snapshots is the round number.
+    let mut start_round = 0;
+    if let Some(monkeys_state) = snapshots.load_resume::<MonkeysState>() {
+        start_round = monkeys_state.round;
+        monkeys_state.apply_to(&mut monkeys_vec);
+    }
+    for round in start_round..10000 {
         // The right way to iterate here would be with the "for in" syntax, but 
         // since i'm modifying the vector inside the cycle i'll be using the index instead.
         for idx in 0..monkeys_vec.len() {
@@ -247,6 +327,7 @@ fn execute (input_path : String)  -> Option<(u64, u64)> {
             // All items has been thrown, clearing the list.
             monkeys_vec[idx].items_vec.clear();
         }
+        snapshots.save_if_at_with((round + 1) as u64, || MonkeysState::from_monkeys(round + 1, &monkeys_vec));
     }
 
     let mut items_inspected_vec = Vec::<i32>::new();
@@ -260,6 +341,7 @@ fn execute (input_path : String)  -> Option<(u64, u64)> {
     println!("multiplying {} with {}", items_inspected_vec[0]  as u64, items_inspected_vec[1] as u64);
     result_part_2 = (items_inspected_vec[0] as u64) * (items_inspected_vec[1] as u64);
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -267,7 +349,12 @@ fn execute (input_path : String)  -> Option<(u64, u64)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 9!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // The rounds can be saved with --snapshot <round> <file> and resumed with --resume <file>.
+    let mut args = DayArgs::from_env();
+    let snapshots = SnapshotOptions::from_args(&mut args);
+    let input_path = args.input_path("./data/input.txt");
+    let results = execute(input_path, &snapshots).unwrap();
     // let results = execute("./data/test.txt".to_string()).unwrap();
     
     println!("\nPart 1 result is {}.", results.0);
@@ -281,6 +368,7 @@ fn main() -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::snapshot;
 
     // // General Test
     // #[test]
@@ -368,6 +456,43 @@ mod tests {
 
      #[test]
      fn global_test_part_2() {
-         assert_eq!(execute("./data/test.txt".to_string()).unwrap().1, 2713310158);
+         assert_eq!(execute("./data/test.txt".to_string(), &SnapshotOptions::default()).unwrap().1, 2713310158);
      }    
+
+    #[test]
+    fn unit_test_rotating_counter_string() {
+        let counter = RotatingCounter::new(350);
+        assert_eq!(counter.to_string(), "0,2,0,0,9,12,10,8,5");
+        assert!(RotatingCounter::from_str("0,2,0").is_err());
+        assert!(RotatingCounter::from_str("2,2,0,0,9,12,10,8,5").is_err());
+        let parsed = RotatingCounter::from_str(&counter.to_string()).unwrap();
+        assert_eq!(parsed.counters, counter.counters);
+    }
+
+    // Snapshots
+    #[test]
+    fn test_snapshot_golden() {
+        let snapshot_path = std::env::temp_dir().join(format!("exercise_11_golden_{}.snap", std::process::id()));
+        let save_options = SnapshotOptions { save_at : Some((1, snapshot_path.clone())), resume_from : None };
+        execute("./data/test.txt".to_string(), &save_options).unwrap();
+
+        let snapshot_string = std::fs::read_to_string(&snapshot_path).unwrap();
+        std::fs::remove_file(snapshot_path).unwrap();
+        snapshot::check_golden(std::path::Path::new("./data/golden/monkeys_round_1.snap"), &snapshot_string).unwrap();
+
+        let restored : MonkeysState = snapshot::from_str(&snapshot_string).unwrap();
+        assert_eq!(snapshot::to_string(&restored), snapshot_string);
+    }
+
+    #[test]
+    fn test_snapshot_resume() {
+        let snapshot_path = std::env::temp_dir().join(format!("exercise_11_resume_{}.snap", std::process::id()));
+        let save_options = SnapshotOptions { save_at : Some((5000, snapshot_path.clone())), resume_from : None };
+        let full_results = execute("./data/test.txt".to_string(), &save_options).unwrap();
+
+        let resume_options = SnapshotOptions { save_at : None, resume_from : Some(snapshot_path.clone()) };
+        let resumed_results = execute("./data/test.txt".to_string(), &resume_options).unwrap();
+        std::fs::remove_file(snapshot_path).unwrap();
+        assert_eq!(resumed_results, full_results);
+    }
 }
\ No newline at end of file
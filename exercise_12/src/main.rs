@@ -3,60 +3,47 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
 
 // // utility
 use std::collections::HashMap;
+use common::geometry::{Dir4, Point2};
+use common::memory;
 
 // 2D graph-like dense matrix structure
 struct WorldMap {
-    world_dimensions : (usize, usize),
+    world_dimensions : Point2,
     elevations_matrix : Vec<u32>,
 }
 
 impl WorldMap {
 
-    fn get_point(&self, coord : (usize, usize)) -> u32 {
+    fn get_point(&self, coord : Point2) -> u32 {
         self.elevations_matrix[self.get_index_from_coords(coord)]
     }
 
-    fn get_coords_from_index(&self, index : &usize) -> (usize, usize) {
-        (index % self.world_dimensions.0, index / self.world_dimensions.0)
+    fn get_coords_from_index(&self, index : &usize) -> Point2 {
+        Point2::from_index(*index, self.world_dimensions.x as usize)
     }
 
-    fn get_index_from_coords(&self, coords : (usize, usize)) -> usize {
-        coords.0 + coords.1 * self.world_dimensions.0
+    fn get_index_from_coords(&self, coords : Point2) -> usize {
+        coords.index_in(&self.world_dimensions).unwrap()
     }
 
-    pub fn is_passable(&self, current_position : (usize, usize), new_position : (usize, usize)) -> bool {
-        if self.get_point(new_position) <= self.get_point(current_position) + 1 {
-            return true;
-        }
-        return false;
+    pub fn is_passable(&self, current_position : Point2, new_position : Point2) -> bool {
+        self.get_point(new_position) <= self.get_point(current_position) + 1
     }
 
     pub fn get_neighbours(&self, current_index : &usize) -> Vec<usize> {
         
-        // For each direction comparing elevations.
-        let mut resulting_elevations = Vec::<usize>::new();
+        // For each direction comparing elevations, skipping what falls outside of the map.
         let current_position = self.get_coords_from_index(current_index);
-
-        if (current_position.1 < self.world_dimensions.1 - 1) && self.is_passable(current_position, (current_position.0, current_position.1 + 1)) {
-            resulting_elevations.push(self.get_index_from_coords((current_position.0, current_position.1 + 1)));
-        }
-
-        if current_position.1 > 0 && self.is_passable(current_position, (current_position.0, current_position.1 - 1)) {
-            resulting_elevations.push(self.get_index_from_coords((current_position.0, current_position.1 - 1)));
-        }
-
-        if (current_position.0 < self.world_dimensions.0 - 1) && self.is_passable(current_position, (current_position.0 + 1, current_position.1)) {
-            resulting_elevations.push(self.get_index_from_coords((current_position.0 + 1, current_position.1)));
-        }
-
-        if current_position.0 > 0 && self.is_passable(current_position, (current_position.0 - 1, current_position.1)) {
-            resulting_elevations.push(self.get_index_from_coords((current_position.0 - 1, current_position.1)));
-        }
-
-        resulting_elevations
+        Dir4::ALL.iter()
+            .map(|&direction| current_position.step(direction))
+            .filter(|&new_position| new_position.is_within(&self.world_dimensions))
+            .filter(|&new_position| self.is_passable(current_position, new_position))
+            .map(|new_position| self.get_index_from_coords(new_position))
+            .collect()
     }
 }
 
@@ -190,11 +177,12 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     println!("Part 1: Calculating the path from index {} to index {}...", starting_point, target_point);
     result_part_1 = 
     run_dijkstra(WorldMap {
-        world_dimensions : (col_number, lines_number),
+        world_dimensions : Point2::from_usize(col_number, lines_number),
         elevations_matrix : elevations_vector.clone()} ,
         starting_point as u32,
         target_point as u32,
     ).unwrap();
+    memory::report_part(1);
 
     // For Part 2 I'll run the same logic from ALL points that have an 'a' (elevation zero)
     // and find the shortest.
@@ -209,7 +197,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 
         // Running Dijkstra on the current position.
         let pixel_distance = run_dijkstra(WorldMap {
-            world_dimensions : (col_number, lines_number),
+            world_dimensions : Point2::from_usize(col_number, lines_number),
             elevations_matrix : elevations_vector.clone()} ,
             index as u32,
             target_point as u32,
@@ -226,6 +214,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     }
     result_part_2 = shortest_route;
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -233,7 +222,9 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 12!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -3,6 +3,10 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
+
+// Memory accounting
+use common::memory;
 
 // utility
 use std::cmp;
@@ -159,6 +163,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
         }
     }
     result_part_1 = good_pairs_counter;
+    memory::report_part(1);
 
     // For Part we must then SORT all packets, removing the blank lines, and adding two new packets to the mix.
     lines_vec.retain(|line| !line.is_empty());
@@ -177,6 +182,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     let index_b = lines_vec.iter().position(|line| line == "[[6]]").unwrap() + 1;
     result_part_2 = (index_a * index_b) as u32;
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -184,7 +190,9 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 13!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -6,6 +6,11 @@ use std::io::{self, prelude::*, BufReader};
 
 // utility
 use std::cmp;
+use common::cli::DayArgs;
+use common::geometry::{Dir8, Point2};
+use common::memory;
+use common::snapshot::{Snapshot, SnapshotError, SnapshotOptions, SnapshotReader, SnapshotWriter};
+use common::stepper::{self, Steppable};
 
 #[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
@@ -17,55 +22,51 @@ enum Materials {
 
 // Defining the 2D space as a dense matrix
 struct SandBox {
-    start : (usize, usize),
-    size : (usize, usize),
+    start : Point2,
+    size : Point2,
     data : Vec<Materials>,
 }
 
 // States of the grain of sand after a movement.
 enum SandMovement{
-    NewPosition((usize, usize)),
+    NewPosition(Point2),
     Stuck,
     Gone
 }
 
 impl SandBox {
 
-    fn new(start : (usize, usize), size : (usize, usize)) -> SandBox{
+    fn new(start : Point2, size : Point2) -> SandBox{
         SandBox {
             start : start,
             size : size,
-            data : vec![Materials::Air; size.0 * size.1],
+            data : vec![Materials::Air; (size.x * size.y) as usize],
         }
     }
 
 
-    fn _get_coords_from_index(&self, index : &usize) -> (usize, usize) {
-        (index % self.size.0 + self.start.0, index / self.size.0 + self.start.1)
+    fn _get_coords_from_index(&self, index : &usize) -> Point2 {
+        Point2::from_index(*index, self.size.x as usize) + self.start
     }
 
 
-    fn get_index_from_coords(&self, coords : (usize, usize)) -> usize {
+    fn get_index_from_coords(&self, coords : Point2) -> usize {
 
-        // Sanity check:
-        if coords.0 < self.start.0 || coords.1 < self.start.1 {
-            panic!("Coordinates before the start of the map! {:?} {:?}",coords, self.start);
+        // Sanity check, with the coordinates relative to the start of the map:
+        match (coords - self.start).index_in(&self.size) {
+            Some(index) => index,
+            None => panic!("Coordinates outside of the map! {:?} {:?} {:?}", coords, self.start, self.size),
         }
-        if coords.0 > self.start.0 + self.size.0 || coords.1 > self.start.1 + self.size.1 {
-            panic!("Coordinates after the end of the map! {:?} {:?} {:?}",coords, self.start, self.size);
-        }
-
-        coords.0.checked_sub(self.start.0).unwrap() + coords.1.checked_sub(self.start.1).unwrap() * self.size.0
     }
 
 
-    fn get_value(&self, coords : (usize, usize)) -> Materials {
+    fn get_value(&self, coords : Point2) -> Materials {
         self.data[self.get_index_from_coords(coords)]
     }
 
 
     // Sets a sand grain but does not perform any gravity simulation.
-    fn add_sand_in_coords(&mut self, coords : (usize, usize)) {
+    fn add_sand_in_coords(&mut self, coords : Point2) {
         let target_index = self.get_index_from_coords(coords);
         self.data[target_index] = Materials::Sand;
     }
@@ -73,17 +74,10 @@ impl SandBox {
 
     // Draws a line of rock in the map.
     // Only for vertical or Horizontal rock segments.
-    fn add_rock_segment(&mut self, start : (usize, usize), end : (usize, usize)) {
-        let difference = (end.0 as i32 - start.0 as i32, end.1 as i32 - start.1 as i32);
-        let direction = (
-            difference.0.checked_div(difference.0.abs()).unwrap_or_else(|| 0),
-            difference.1.checked_div(difference.1.abs()).unwrap_or_else(|| 0));
-
-        for index in 0..cmp::max(difference.0.abs(), difference.1.abs()) + 1 {
-            let new_coords = (
-                (start.0 as i32 + (index * direction.0)) as usize,
-                (start.1 as i32 + (index * direction.1)) as usize);
-            let data_index = self.get_index_from_coords(new_coords);
+    fn add_rock_segment(&mut self, start : Point2, end : Point2) {
+        let direction = (end - start).signum();
+        for index in 0..start.chebyshev(&end) + 1 {
+            let data_index = self.get_index_from_coords(start + direction * index);
             self.data[data_index] = Materials::Rock;
         }
     }
@@ -93,11 +87,11 @@ impl SandBox {
     // - the grain either reachesa static place (Stuck) OR
     // - it falls to the bottom (Gone) OR 
     // - it cannot be spawned at all because the drop position is occupied (Gone)
-    fn drop_sand_grain(&mut self, add_position : (usize, usize)) -> (usize, Option<(usize, usize)>) {
+    fn drop_sand_grain(&mut self, add_position : Point2) -> (usize, Option<Point2>) {
 
         // Looping until the grain has stopped moving or has reached the bottom.
         let mut sand_cursor = add_position;
-        for counter in 0..self.size.1 + 1 {
+        for counter in 0..self.size.y as usize + 1 {
             match self.get_sand_direction(sand_cursor) {
                 SandMovement::NewPosition(new_position) => sand_cursor = new_position,
                 SandMovement::Stuck => return (counter , Some(sand_cursor)),
@@ -112,15 +106,17 @@ impl SandBox {
 
     // Keeps adding sand to the sandbox, until the first grain is Gone instead
     // of Stuck. At that point it returns the number of sand grains.
-    fn add_all_sand(&mut self, add_position : (usize, usize)) -> usize{
+    // The grains already in the sandbox (when resuming a snapshot) are counted too.
+    fn add_all_sand(&mut self, add_position : Point2, snapshots : &SnapshotOptions) -> usize{
 
         // Looping until found.
-        let mut sand_counter = 0;
+        let mut sand_counter = self.data.iter().filter(|&&material| material == Materials::Sand).count();
         loop {
             match self.drop_sand_grain(add_position).1 {
                 Some(sand_position) => {
                     self.add_sand_in_coords(sand_position);
-                    sand_counter += 1;},
+                    sand_counter += 1;
+                    snapshots.save_if_at(sand_counter as u64, self);},
                 None => return sand_counter,
             }
         }
@@ -128,63 +124,53 @@ impl SandBox {
 
 
     // Checking in the sandbox what's below, provides the next positoin for the grain.
-    fn get_sand_direction(&self, curr_position: (usize, usize)) -> SandMovement {
-
-        let mut new_position : (usize, usize) = curr_position; 
+    fn get_sand_direction(&self, curr_position: Point2) -> SandMovement {
 
         // Checking if it has reached the bottom of the map:
-        if new_position.1 >= self.start.1 + self.size.1 - 1 {
+        if curr_position.y >= self.start.y + self.size.y - 1 {
             return SandMovement::Gone;
         }
 
         // Check the three objects below: first straight below, then bottom left, then bottom right.
-        if self.get_value((curr_position.0, curr_position.1 + 1)) == Materials::Air {
-            new_position = (curr_position.0, curr_position.1 + 1);
-        }
-
-        // Bottom left
-        else if curr_position.0 > self.start.0 && curr_position.1 < self.start.1 + self.size.1 && 
-            self.get_value((curr_position.0 - 1, curr_position.1 + 1)) == Materials::Air {
-                new_position = (curr_position.0.checked_sub(1).unwrap(), curr_position.1 + 1);
-        }
-
-        // Bottom right
-        else if curr_position.0 < self.start.0 + self.size.0 && curr_position.1 < self.start.1 + self.size.1 &&
-            self.get_value((curr_position.0 + 1, curr_position.1 + 1)) == Materials::Air {
-                new_position = (curr_position.0 + 1, curr_position.1 + 1);
+        // Whatever falls outside of the map on the sides is not a valid position.
+        for direction in [Dir8::Down, Dir8::DownLeft, Dir8::DownRight] {
+            let new_position = curr_position + direction.delta();
+            if (new_position - self.start).is_within(&self.size) &&
+                self.get_value(new_position) == Materials::Air {
+                return SandMovement::NewPosition(new_position);
+            }
         }
 
         // otherwise it's stuck.
-        else {
-
-            // Checking if already overlapping an existing sand:
-            if self.get_value((curr_position.0, curr_position.1)) != Materials::Air {
-                return SandMovement::Gone;
-            }
-
-            // Otherwise, it's legit stuck
-            return SandMovement::Stuck;
+        // Checking if already overlapping an existing sand:
+        if self.get_value(curr_position) != Materials::Air {
+            return SandMovement::Gone;
         }
 
-        // If arrived here all the check cases have been passed.
-        SandMovement::NewPosition(new_position)
+        // Otherwise, it's legit stuck
+        SandMovement::Stuck
     }
 
 
-    // Generates a string with the sandbox.
-    fn _draw_map (&self) -> String {
-        let mut out_string = "".to_string();
-        for row_index in 0..self.size.1 {
-            let mut data_slice = vec![Materials::Air; self.size.0];
-            data_slice.copy_from_slice(&self.data[row_index * self.size.0..(row_index + 1) * self.size.0]);
-            let new_string = data_slice.iter().map(|&val| {
+    // Generates a string for each row of the sandbox.
+    fn draw_rows (&self) -> Vec<String> {
+        self.data.chunks(self.size.x as usize).map(|data_slice| {
+            data_slice.iter().map(|&val| {
                 match &val {
                     &Materials::Air => '.',
                     &Materials::Rock => '#',
                     &Materials::Sand => 'o',
                 }
-            }).collect::<String>().clone(); 
-            out_string += &new_string;
+            }).collect::<String>()
+        }).collect()
+    }
+
+
+    // Generates a string with the sandbox.
+    fn draw_map (&self) -> String {
+        let mut out_string = "".to_string();
+        for row in self.draw_rows() {
+            out_string += &row;
             out_string += "\n";
         }
 
@@ -192,17 +178,48 @@ impl SandBox {
     } 
 }
 
-// Primary Function
-fn execute (input_path : String)  -> Option<(u32, u32)> {
+
+impl Snapshot for SandBox {
+    const KIND : &'static str = "sandbox";
+
+    fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+        writer.list("start", [self.start.x, self.start.y]);
+        writer.list("size", [self.size.x, self.size.y]);
+        writer.grid("data", &self.draw_rows());
+    }
+
+    fn read_snapshot(reader : &mut SnapshotReader) -> Result<SandBox, SnapshotError> {
+        let start : Vec<i32> = reader.list("start")?;
+        let size : Vec<i32> = reader.list("size")?;
+        if start.len() != 2 || size.len() != 2 || size[0] <= 0 || size[1] <= 0 {
+            return Err(reader.error("start and size must be two coordinates, with a positive size".to_string()));
+        }
+        let mut sandbox = SandBox::new(Point2::new(start[0], start[1]), Point2::new(size[0], size[1]));
+
+        let rows = reader.grid("data")?;
+        if rows.len() != sandbox.size.y as usize || rows.iter().any(|row| row.len() != sandbox.size.x as usize) {
+            return Err(reader.error("the data rows do not match the size".to_string()));
+        }
+        for (index, cell) in rows.iter().flat_map(|row| row.chars()).enumerate() {
+            sandbox.data[index] = match cell {
+                '.' => Materials::Air,
+                '#' => Materials::Rock,
+                'o' => Materials::Sand,
+                _ => return Err(reader.error(format!("unknown material '{}'", cell))),
+            };
+        }
+        Ok(sandbox)
+    }
+}
+
+// Reading the rock paths, each line is made of coordinates separated by " -> ".
+// Returns the paths with the minimum and maximum coordinates of the cave of part 1.
+fn read_rock_paths(input_path : &str) -> (Vec<Vec<Point2>>, Point2, Point2) {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
-    // Results variables:
-    let result_part_1 : u32;
-    let result_part_2 : u32;
-
     // First reading the input string - easy.
     let mut lines_vec = Vec::<String>::new();
     // Finally reading the stuff.
@@ -214,43 +231,109 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     println!("read {} lines from input", lines_vec.len());
     assert!(lines_vec.len() > 1);
 
-    // Each line is made of coordinates separated by " -> "
     // Finding the map dimensions:
-    let mut rock_paths = Vec::<Vec<(usize, usize)>>::new();
-    let mut min_dimensions = (usize::MAX, 0);
-    let mut max_dimensions = (0, 0);
+    let mut rock_paths = Vec::<Vec<Point2>>::new();
+    let mut min_dimensions = Point2::new(i32::MAX, 0);
+    let mut max_dimensions = Point2::zero();
     for curr_line in lines_vec {
-        rock_paths.push(Vec::<(usize, usize)>::new());
+        rock_paths.push(Vec::<Point2>::new());
         for pair in curr_line.split(" -> ") {
             let dimensions = pair.split_once(",").unwrap().clone();
-            let dimensions = (dimensions.0.parse::<usize>().unwrap(), dimensions.1.parse::<usize>().unwrap());
-            min_dimensions.0 = cmp::min(min_dimensions.0, dimensions.0 - 1); 
-            // min_dimensions.1 = cmp::min(min_dimensions.1, dimensions.1); // Unnecessary
-            max_dimensions.0 = cmp::max(max_dimensions.0, dimensions.0); 
-            max_dimensions.1 = cmp::max(max_dimensions.1, dimensions.1); 
+            let dimensions = Point2::new(dimensions.0.parse::<i32>().unwrap(), dimensions.1.parse::<i32>().unwrap());
+            min_dimensions.x = cmp::min(min_dimensions.x, dimensions.x - 1); 
+            // min_dimensions.y = cmp::min(min_dimensions.y, dimensions.y); // Unnecessary
+            max_dimensions.x = cmp::max(max_dimensions.x, dimensions.x); 
+            max_dimensions.y = cmp::max(max_dimensions.y, dimensions.y); 
             rock_paths.last_mut().unwrap().push(dimensions);
         }
         assert!(!rock_paths.is_empty());
     }
+    (rock_paths, min_dimensions, max_dimensions)
+}
 
-    // Creating the map and filling it.
-    let cave_size = (
-        max_dimensions.0.checked_sub(min_dimensions.0).unwrap() + 1,
-        max_dimensions.1.checked_sub(min_dimensions.1).unwrap() + 1);
-    println!("For Part 1: Creating cave of size {:?}", cave_size);
-    let mut cave_map = SandBox::new(
-        min_dimensions,
-        cave_size);  
-    for line_points in rock_paths.clone() {
+
+// Creating the map of a cave and filling it with the rocks.
+fn make_cave(rock_paths : &[Vec<Point2>], start : Point2, size : Point2) -> SandBox {
+    let mut cave_map = SandBox::new(start, size);
+    for line_points in rock_paths {
         for segment_idx in 1..line_points.len() {
             cave_map.add_rock_segment(line_points[segment_idx - 1], line_points[segment_idx]);
         }
     }
+    cave_map
+}
+
 
-    // Pouring all the sand from 500, 0, as required
-    let pouring_point = (500, 0);
-    let grains_number = cave_map.add_all_sand(pouring_point);
+// The cave of part 1 filled one grain per step, for the stepper.
+struct SandStepper {
+    cave_map : SandBox,
+    pouring_point : Point2,
+    grains_number : u64,
+    last_grain : Option<(Point2, usize)>, // Where the last grain stopped, after how many moves.
+    is_full : bool,
+}
+
+impl Steppable for SandStepper {
+    fn step(&mut self) -> bool {
+        if self.is_full {
+            return false;
+        }
+        match self.cave_map.drop_sand_grain(self.pouring_point) {
+            (moves_number, Some(sand_position)) => {
+                self.cave_map.add_sand_in_coords(sand_position);
+                self.grains_number += 1;
+                self.last_grain = Some((sand_position, moves_number));
+            },
+            (_, None) => self.is_full = true,
+        }
+        true
+    }
+
+    fn step_number(&self) -> u64 {
+        self.grains_number
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        let (last_grain, last_moves) = match self.last_grain {
+            Some((position, moves_number)) => (format!("{},{}", position.x, position.y), moves_number.to_string()),
+            None => ("none".to_string(), "0".to_string()),
+        };
+        vec![
+            ("grains".to_string(), self.grains_number.to_string()),
+            ("last_grain".to_string(), last_grain),
+            ("last_moves".to_string(), last_moves),
+            ("full".to_string(), self.is_full.to_string()),
+        ]
+    }
+
+    fn draw(&self) -> String {
+        self.cave_map.draw_map()
+    }
+}
+
+
+// Primary Function
+fn execute (input_path : String, snapshots : &SnapshotOptions)  -> Option<(u32, u32)> {
+
+    // Results variables:
+    let result_part_1 : u32;
+    let result_part_2 : u32;
+
+    let (rock_paths, mut min_dimensions, mut max_dimensions) = read_rock_paths(&input_path);
+
+    // Creating the map and filling it, unless it is restored from a snapshot.
+    let cave_size = max_dimensions - min_dimensions + Point2::new(1, 1);
+    println!("For Part 1: Creating cave of size {:?}", cave_size);
+    let mut cave_map = match snapshots.load_resume::<SandBox>() {
+        Some(saved_map) => saved_map,
+        None => make_cave(&rock_paths, min_dimensions, cave_size),
+    };
+
+    // Pouring all the sand from 500, 0, as required. The step of the snapshots is the number of grains.
+    let pouring_point = Point2::new(500, 0);
+    let grains_number = cave_map.add_all_sand(pouring_point, snapshots);
     result_part_1 = grains_number as u32;
+    memory::report_part(1);
     
     // Debug only, for the test sized input or for a good laugh.
     //println!("Testing map:\n{}",cave_map.draw_map());
@@ -259,32 +342,24 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     // more convenient here! Let's continue like this. 
     // We have to add a bottom to the map. It doesn't have to be infinitely wide, 
     // just twice as wide as it is tall.
-    max_dimensions.1 = max_dimensions.1 + 2;
-    max_dimensions.0 = pouring_point.0 + cave_size.0 + max_dimensions.1;
-    min_dimensions.0 = pouring_point.0 - cave_size.0 - max_dimensions.1;
-    let cave_size = (
-        max_dimensions.0.checked_sub(min_dimensions.0).unwrap() + 1,
-        max_dimensions.1.checked_sub(min_dimensions.1).unwrap() + 1);
+    max_dimensions.y = max_dimensions.y + 2;
+    max_dimensions.x = pouring_point.x + cave_size.x + max_dimensions.y;
+    min_dimensions.x = pouring_point.x - cave_size.x - max_dimensions.y;
+    let cave_size = max_dimensions - min_dimensions + Point2::new(1, 1);
     println!("For Part 1: Creating cave of size {:?}", cave_size);
-    let mut cave_map = SandBox::new(
-        min_dimensions,
-        cave_size);  
-    for line_points in rock_paths.clone() {
-        for segment_idx in 1..line_points.len() {
-            cave_map.add_rock_segment(line_points[segment_idx - 1], line_points[segment_idx]);
-        }
-    }
+    let mut cave_map = make_cave(&rock_paths, min_dimensions, cave_size);
 
     // Adding a bottom segment:
-    cave_map.add_rock_segment((min_dimensions.0, max_dimensions.1), (max_dimensions.0, max_dimensions.1));
+    cave_map.add_rock_segment(Point2::new(min_dimensions.x, max_dimensions.y), max_dimensions);
 
     // Filling with sand again.
-    let grains_number = cave_map.add_all_sand(pouring_point);
+    let grains_number = cave_map.add_all_sand(pouring_point, &SnapshotOptions::default());
     result_part_2 = grains_number as u32;
 
     // Debug only, for the test sized input or for a good laugh.
     //println!("Testing map:\n{}",cave_map.draw_map());
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -292,7 +367,20 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 14!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // The simulation of part 1 can be saved with --snapshot <grains> <file> and resumed with --resume <file>,
+    // or run interactively with --step.
+    let mut args = DayArgs::from_env();
+    let snapshots = SnapshotOptions::from_args(&mut args);
+    let step_mode = args.take_switch("--step");
+    let input_path = args.input_path("./data/input.txt");
+    if step_mode {
+        let (rock_paths, min_dimensions, max_dimensions) = read_rock_paths(&input_path);
+        let cave_map = make_cave(&rock_paths, min_dimensions, max_dimensions - min_dimensions + Point2::new(1, 1));
+        let mut sand_stepper = SandStepper { cave_map, pouring_point : Point2::new(500, 0), grains_number : 0, last_grain : None, is_full : false };
+        return stepper::run_stepper(&mut sand_stepper, io::stdin().lock(), &mut io::stdout());
+    }
+    let results = execute(input_path, &snapshots).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -305,15 +393,57 @@ fn main() -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::snapshot;
 
     // General Test
     #[test]
     fn global_test_part_1() {
-        assert_eq!(execute("./data/test.txt".to_string()).unwrap().0, 24);
+        assert_eq!(execute("./data/test.txt".to_string(), &SnapshotOptions::default()).unwrap().0, 24);
     }    
 
     #[test]
     fn global_test_part_2() {
-        assert_eq!(execute("./data/test.txt".to_string()).unwrap().1, 93);
+        assert_eq!(execute("./data/test.txt".to_string(), &SnapshotOptions::default()).unwrap().1, 93);
     }    
-}
\ No newline at end of file
+
+    // Snapshots
+    #[test]
+    fn test_snapshot_golden() {
+        let snapshot_path = std::env::temp_dir().join(format!("exercise_14_golden_{}.snap", std::process::id()));
+        let save_options = SnapshotOptions { save_at : Some((5, snapshot_path.clone())), resume_from : None };
+        execute("./data/test.txt".to_string(), &save_options).unwrap();
+
+        let snapshot_string = std::fs::read_to_string(&snapshot_path).unwrap();
+        std::fs::remove_file(snapshot_path).unwrap();
+        snapshot::check_golden(std::path::Path::new("./data/golden/sandbox_5_grains.snap"), &snapshot_string).unwrap();
+
+        let restored : SandBox = snapshot::from_str(&snapshot_string).unwrap();
+        assert_eq!(snapshot::to_string(&restored), snapshot_string);
+    }
+
+    #[test]
+    fn test_snapshot_resume() {
+        let snapshot_path = std::env::temp_dir().join(format!("exercise_14_resume_{}.snap", std::process::id()));
+        let save_options = SnapshotOptions { save_at : Some((12, snapshot_path.clone())), resume_from : None };
+        let full_results = execute("./data/test.txt".to_string(), &save_options).unwrap();
+
+        let resume_options = SnapshotOptions { save_at : None, resume_from : Some(snapshot_path.clone()) };
+        let resumed_results = execute("./data/test.txt".to_string(), &resume_options).unwrap();
+        std::fs::remove_file(snapshot_path).unwrap();
+        assert_eq!(resumed_results, full_results);
+    }
+
+    // Stepper
+    #[test]
+    fn test_sand_stepper() {
+        let (rock_paths, min_dimensions, max_dimensions) = read_rock_paths("./data/test.txt");
+        let cave_map = make_cave(&rock_paths, min_dimensions, max_dimensions - min_dimensions + Point2::new(1, 1));
+        let mut sand_stepper = SandStepper { cave_map, pouring_point : Point2::new(500, 0), grains_number : 0, last_grain : None, is_full : false };
+
+        stepper::execute_command(&mut sand_stepper, "step").unwrap();
+        assert_eq!(sand_stepper.last_grain, Some((Point2::new(500, 8), 8)));
+        stepper::execute_command(&mut sand_stepper, "until full == true").unwrap();
+        assert_eq!(sand_stepper.grains_number, 24);
+        assert!(sand_stepper.draw().contains("o#"));
+    }
+}
@@ -7,10 +7,15 @@ use std::io::{self, prelude::*, BufReader};
 // utility
 use std::cmp;
 use regex::Regex;
+use common::checkpoint::CheckpointOptions;
+use common::cli::DayArgs;
+use common::geometry::Point2;
+use common::memory;
+use common::snapshot::{Snapshot, SnapshotError, SnapshotReader, SnapshotWriter};
 
 
 // Same structure of the code from Day 4 with different regex. 
-fn parse_locations(input : &str) -> Option<((i32, i32), (i32, i32))> {
+fn parse_locations(input : &str) -> Option<(Point2, Point2)> {
 
     let regex_string = 
         r"\D+=(?P<val1>\-*\d+)\D+=(?P<val2>\-*\d+)\D+=(?P<val3>\-*\d+)\D+=(?P<val4>\-*\d+)";
@@ -26,7 +31,7 @@ fn parse_locations(input : &str) -> Option<((i32, i32), (i32, i32))> {
             let val2 = internal_parse("val2");
             let val3 = internal_parse("val3");
             let val4 = internal_parse("val4");
-            Some(((val1, val2), (val3, val4)))
+            Some((Point2::new(val1, val2), Point2::new(val3, val4)))
         }
         None => None,
     }
@@ -168,22 +173,22 @@ impl Intervals {
 
 // Given the beacons at certain distances, it creates the "exclusion" intervals.
 fn make_exclusion_zone (
-    input_positions : &Vec<((i32, i32), (i32, i32))>, 
+    input_positions : &Vec<(Point2, Point2)>, 
     test_line : i32) -> Intervals {
 
     let mut intervals_struct = Intervals::new();
     for element in input_positions {
         // Measuring Manhattan distance from its beacon:
-        let distance_to_beacon = (element.0.0 - element.1.0).abs() + (element.0.1 - element.1.1).abs();
+        let distance_to_beacon = element.0.manhattan(&element.1);
 
         // If the distance is less than the distance from the test line, skipping.
-        let intersection_with_line = distance_to_beacon - (element.0.1 - test_line).abs();
+        let intersection_with_line = distance_to_beacon - (element.0.y - test_line).abs();
         if intersection_with_line <= 0 {
             continue;
         }
 
         // Otherwise, applying the exclusion.
-        intervals_struct.add_interval((element.0.0 - intersection_with_line, element.0.0 + intersection_with_line));
+        intervals_struct.add_interval((element.0.x - intersection_with_line, element.0.x + intersection_with_line));
     }
 
     // Retrieving the struct.
@@ -191,8 +196,34 @@ fn make_exclusion_zone (
 }
 
 
+// The progress of the part 2 search, saved periodically.
+struct SearchCheckpoint {
+    square_side : u32,
+    next_line : u32,
+    found_slots : Vec<u64>,
+}
+
+impl Snapshot for SearchCheckpoint {
+    const KIND : &'static str = "day_15_search";
+
+    fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+        writer.value("square_side", self.square_side);
+        writer.value("next_line", self.next_line);
+        writer.list("found_slots", &self.found_slots);
+    }
+
+    fn read_snapshot(reader : &mut SnapshotReader) -> Result<SearchCheckpoint, SnapshotError> {
+        Ok(SearchCheckpoint {
+            square_side : reader.value("square_side")?,
+            next_line : reader.value("next_line")?,
+            found_slots : reader.list("found_slots")?,
+        })
+    }
+}
+
+
 // Primary Function
-fn execute (input_path : String, test_line : i32, square_side : u32)  -> Option<(u64, u64)> {
+fn execute (input_path : String, test_line : i32, square_side : u32, checkpoints : &CheckpointOptions)  -> Option<(u64, u64)> {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
@@ -214,7 +245,7 @@ fn execute (input_path : String, test_line : i32, square_side : u32)  -> Option<
     assert!(!lines_vec.is_empty());
 
     // Parsing each line, retrieving the two sets of coordinates.
-    let mut sensors_and_beacons = Vec::<((i32, i32), (i32, i32))>::new();
+    let mut sensors_and_beacons = Vec::<(Point2, Point2)>::new();
     for line in lines_vec {
         if let Some(coords) = parse_locations(&line) {
             sensors_and_beacons.push(coords);
@@ -232,17 +263,25 @@ fn execute (input_path : String, test_line : i32, square_side : u32)  -> Option<
     // counting the remaining spaces that MUST be empty.
     let mut occupied_spaces = Vec::<i32>::new();
     for element in &sensors_and_beacons {
-        if element.0.1 == test_line {occupied_spaces.push(element.0.0);};
-        if element.1.1 == test_line {occupied_spaces.push(element.1.0);};
+        if element.0.y == test_line {occupied_spaces.push(element.0.x);};
+        if element.1.y == test_line {occupied_spaces.push(element.1.x);};
     }
     occupied_spaces.sort();
     occupied_spaces.dedup();
     result_part_1 = intervals_part_1.get_total_size().checked_sub(occupied_spaces.len() as u32).unwrap() as u64;
+    memory::report_part(1);
 
     // For part 2, the search is performed on a 4 millions x 4 millions square area.
     // The optimization done above should work here.
+    // The search can restart from a checkpoint, with the slots found before it.
     let mut found_slots = Vec::<u64>::new();
-    for line_idx in 0..square_side as i32 {
+    let mut start_line = 0;
+    if let Some(checkpoint) = checkpoints.load_resume::<SearchCheckpoint>() {
+        assert!(checkpoint.square_side == square_side, "The checkpoint is for a square of side {}", checkpoint.square_side);
+        start_line = checkpoint.next_line;
+        found_slots = checkpoint.found_slots;
+    }
+    for line_idx in start_line as i32..square_side as i32 {
         let mut current_interval = make_exclusion_zone(
             &sensors_and_beacons, 
             line_idx as i32);
@@ -266,12 +305,18 @@ fn execute (input_path : String, test_line : i32, square_side : u32)  -> Option<
         if line_idx % 1000000 == 0 {
             println!("parsing line {}", line_idx);
         }
+
+        checkpoints.save_if_due((line_idx + 1) as u64, || SearchCheckpoint {
+            square_side,
+            next_line : (line_idx + 1) as u32,
+            found_slots : found_slots.clone() });
     }
 
     // There should only be ONE point remaining!
     assert!(found_slots.len() == 1);
     result_part_2 = found_slots[0];
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -280,8 +325,14 @@ fn execute (input_path : String, test_line : i32, square_side : u32)  -> Option<
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 15!");
 
-    //let results = execute("./data/test.txt".to_string(), 10, 20).unwrap();
-    let results = execute("./data/input.txt".to_string(), 2000000, 4000000).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // The search of part 2 can be checkpointed with --checkpoint <file> [--checkpoint-every <lines>]
+    // and continued with --resume <file>.
+    let mut args = DayArgs::from_env();
+    let checkpoints = CheckpointOptions::from_args(&mut args, 100000);
+    let input_path = args.input_path("./data/input.txt");
+    //let results = execute("./data/test.txt".to_string(), 10, 20, &CheckpointOptions::disabled()).unwrap();
+    let results = execute(input_path, 2000000, 4000000, &checkpoints).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -298,11 +349,30 @@ mod tests {
     // General Test
     #[test]
     fn global_test_part_1() {
-        assert_eq!(execute("./data/test.txt".to_string(), 10, 4000000).unwrap().0, 26);
+        assert_eq!(execute("./data/test.txt".to_string(), 10, 4000000, &CheckpointOptions::disabled()).unwrap().0, 26);
     }    
 
     #[test]
     fn global_test_part_2() {
-        assert_eq!(execute("./data/test.txt".to_string(), 10, 20).unwrap().1, 291);
+        assert_eq!(execute("./data/test.txt".to_string(), 10, 20, &CheckpointOptions::disabled()).unwrap().1, 291);
     }    
+
+    // Checkpoints: resuming from a checkpoint taken after the slot was found,
+    // and from one taken before.
+    #[test]
+    fn test_checkpoint_resume() {
+        for (checkpoint_line, name) in [(15, "after"), (11, "before")] {
+            let checkpoint_path = std::env::temp_dir().join(format!("exercise_15_{}_{}.snap", name, std::process::id()));
+            let checkpoints = CheckpointOptions { path : Some(checkpoint_path.clone()), resume_from : None, interval : checkpoint_line };
+            execute("./data/test.txt".to_string(), 10, 20, &checkpoints).unwrap();
+
+            let checkpoint : SearchCheckpoint = common::snapshot::load(&checkpoint_path).unwrap();
+            assert_eq!(checkpoint.next_line as u64, checkpoint_line);
+
+            let resume_options = CheckpointOptions { path : None, resume_from : Some(checkpoint_path.clone()), interval : 1000 };
+            let resumed_results = execute("./data/test.txt".to_string(), 10, 20, &resume_options).unwrap();
+            std::fs::remove_file(checkpoint_path).unwrap();
+            assert_eq!(resumed_results.1, 291);
+        }
+    }
 }
\ No newline at end of file
@@ -8,6 +8,10 @@ use std::io::{self, prelude::*, BufReader};
 use std::collections::HashMap;
 use regex::Regex;
 use std::time::Instant;
+use common::checkpoint::CheckpointOptions;
+use common::cli::DayArgs;
+use common::memory;
+use common::snapshot::{Snapshot, SnapshotError, SnapshotReader, SnapshotWriter};
 
 // Implementing Dijkstra's algoritm (Similar to Day 12)
 // TODO move it in a "utilities" with generic type.
@@ -279,8 +283,34 @@ fn find_path_maximum_steam (
 }
 
 
+// The progress of the part 2 search on the subsets, saved periodically.
+struct SubsetsCheckpoint {
+    valves_number : usize,
+    next_subset : u64,
+    max_steam : u32,
+}
+
+impl Snapshot for SubsetsCheckpoint {
+    const KIND : &'static str = "day_16_subsets";
+
+    fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+        writer.value("valves_number", self.valves_number);
+        writer.value("next_subset", self.next_subset);
+        writer.value("max_steam", self.max_steam);
+    }
+
+    fn read_snapshot(reader : &mut SnapshotReader) -> Result<SubsetsCheckpoint, SnapshotError> {
+        Ok(SubsetsCheckpoint {
+            valves_number : reader.value("valves_number")?,
+            next_subset : reader.value("next_subset")?,
+            max_steam : reader.value("max_steam")?,
+        })
+    }
+}
+
+
 // Primary Function
-fn execute (input_path : String)  -> Option<(u32, u32)> {
+fn execute (input_path : String, checkpoints : &CheckpointOptions)  -> Option<(u32, u32)> {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
@@ -333,15 +363,26 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
         max_iterations + 1 /* For the valve to open */);
     println!("Path taken is {:?} for a total of {} steam.", path_taken, max_steam);
     result_part_1 = max_steam;  
+    memory::report_part(1);
     println!("Part A took {} ms", now.elapsed().as_millis());
 
     // For two actors, using a dumb but very feasible approach: iterating on all the possible pairs
     // of subsets of the valves. Each time we got to re-calculate the distances, run the find function
     // and look for the faster.
+    // The valves are sorted by name so that the subset indexes are the same at every run,
+    // which makes the checkpoints valid across runs.
     let mut max_steam_two_actors = 0;
+    let mut start_subset = 0;
+    if let Some(checkpoint) = checkpoints.load_resume::<SubsetsCheckpoint>() {
+        assert!(checkpoint.valves_number == valves_map.len(), "The checkpoint is for {} valves", checkpoint.valves_number);
+        start_subset = checkpoint.next_subset as i32;
+        max_steam_two_actors = checkpoint.max_steam;
+    }
+    let mut valves_names : Vec<&String> = valves_map.keys().collect();
+    valves_names.sort();
     let max_iterations = 26;
     let now = Instant::now();
-    for subset_idx in 0..i32::pow(2, (valves_map.len() - 1) as u32) {
+    for subset_idx in start_subset..i32::pow(2, (valves_map.len() - 1) as u32) {
 
         if subset_idx % 100 == 0 {
             println!("iteration {} of {}", subset_idx, i32::pow(2, (valves_map.len() - 1) as u32));
@@ -351,12 +392,12 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
         // the algo won't have to go through them.
         let mut path_a = Vec::<String>::new();
         let mut path_b = Vec::<String>::new();
-        for (elem_index, elem) in valves_map.iter().enumerate() {
+        for (elem_index, elem) in valves_names.iter().enumerate() {
             if subset_idx / i32::pow(2, elem_index as u32) % 2 == 0{
-                path_a.push(elem.0.clone());
+                path_a.push((*elem).clone());
             }
             else {
-                path_b.push(elem.0.clone());
+                path_b.push((*elem).clone());
             }
         }
 
@@ -383,10 +424,16 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
         if max_steam_two_actors < max_steam_a + max_steam_b {
            max_steam_two_actors = max_steam_a + max_steam_b;
         }
+
+        checkpoints.save_if_due((subset_idx + 1) as u64, || SubsetsCheckpoint {
+            valves_number : valves_map.len(),
+            next_subset : (subset_idx + 1) as u64,
+            max_steam : max_steam_two_actors });
     }
     println!("Part B took {} ms", now.elapsed().as_millis());
 
     result_part_2 = max_steam_two_actors;
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -394,7 +441,13 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 16!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // The search of part 2 can be checkpointed with --checkpoint <file> [--checkpoint-every <subsets>]
+    // and continued with --resume <file>.
+    let mut args = DayArgs::from_env();
+    let checkpoints = CheckpointOptions::from_args(&mut args, 1000);
+    let input_path = args.input_path("./data/input.txt");
+    let results = execute(input_path, &checkpoints).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -411,11 +464,27 @@ mod tests {
     // General Test
     #[test]
     fn global_test_part_1() {
-        assert_eq!(execute("./data/test.txt".to_string()).unwrap().0, 1651);
+        assert_eq!(execute("./data/test.txt".to_string(), &CheckpointOptions::disabled()).unwrap().0, 1651);
     }    
 
     #[test]
     fn global_test_part_2() {
-        assert_eq!(execute("./data/test.txt".to_string()).unwrap().1, 1707);
+        assert_eq!(execute("./data/test.txt".to_string(), &CheckpointOptions::disabled()).unwrap().1, 1707);
     }    
+
+    // Checkpoints: one every 30 subsets, so the last of the 64 is written at subset 60.
+    #[test]
+    fn test_checkpoint_resume() {
+        let checkpoint_path = std::env::temp_dir().join(format!("exercise_16_{}.snap", std::process::id()));
+        let checkpoints = CheckpointOptions { path : Some(checkpoint_path.clone()), resume_from : None, interval : 30 };
+        execute("./data/test.txt".to_string(), &checkpoints).unwrap();
+
+        let checkpoint : SubsetsCheckpoint = common::snapshot::load(&checkpoint_path).unwrap();
+        assert_eq!(checkpoint.next_subset, 60);
+
+        let resume_options = CheckpointOptions { path : None, resume_from : Some(checkpoint_path.clone()), interval : 1000 };
+        let resumed_results = execute("./data/test.txt".to_string(), &resume_options).unwrap();
+        std::fs::remove_file(checkpoint_path).unwrap();
+        assert_eq!(resumed_results.1, 1707);
+    }
 }
\ No newline at end of file
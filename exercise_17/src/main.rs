@@ -4,13 +4,12 @@
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
 
-#[derive(PartialEq)]
-#[derive(Debug)]
-enum Directions {
-    Left,
-    Right,
-    Down,
-}
+// utility
+use common::cli::DayArgs;
+use common::geometry::{Dir4, Point2};
+use common::memory;
+use common::snapshot::{Snapshot, SnapshotError, SnapshotOptions, SnapshotReader, SnapshotWriter};
+use common::stepper::{self, Steppable};
 
 #[derive(PartialEq)]
 enum BlockType {
@@ -24,12 +23,12 @@ enum BlockType {
 #[derive(Clone)]
 struct TetrisBlock {
     // Blocks are defined from the bottom-left corner.
+    // The position y is the altitude, growing upwards.
     shape : Vec<Vec<bool>>,
-    altitude : u32,
-    x_pos : u32,
+    position : Point2,
 }
 impl TetrisBlock {
-    fn new(block_type : BlockType, altitude : u32, pos : u32) -> TetrisBlock {
+    fn new(block_type : BlockType, position : Point2) -> TetrisBlock {
         // Creating the vec based on the shape:
         let mut block = Vec::<Vec<bool>>::new();
         match block_type {
@@ -45,20 +44,19 @@ impl TetrisBlock {
                 block.push(vec![false, true, false]);},
             BlockType::Square => {
                 block.push(vec![true, true]);
-                block.push(vec![true, true]);
-                vec![[true, true], [true, true]];},
+                block.push(vec![true, true]);},
             BlockType::El => {
                 block.push(vec![true, true, true]);
                 block.push(vec![false, false, true]);
                 block.push(vec![false, false, true]);},
         };
-        TetrisBlock{shape : block, altitude : altitude, x_pos : pos}
+        TetrisBlock{shape : block, position : position}
     }
 
 
-    fn try_move_block(&mut self, direction : &Directions, board : &TetrisBoard) -> Option<Vec<Vec<bool>>> {
+    fn try_move_block(&mut self, direction : Dir4, board : &TetrisBoard) -> Option<Vec<Vec<bool>>> {
         
-        if self.collision_with_borders(board.get_width(), &direction) ||
+        if self.collision_with_borders(board.get_width(), direction) ||
             self.collision_with_map(board, direction){
                 return Some(self.add_block_to_map(board));
             }
@@ -68,12 +66,8 @@ impl TetrisBlock {
     }
 
 
-    fn move_block(&mut self, direction : &Directions) {
-        match direction {
-            &Directions::Left => self.x_pos -= 1,
-            &Directions::Right => self.x_pos += 1,
-            &Directions::Down => self.altitude -= 1,
-        };
+    fn move_block(&mut self, direction : Dir4) {
+        self.position += direction.delta_y_up();
     }
 
 
@@ -81,14 +75,15 @@ impl TetrisBlock {
 
         // Creating the new board adding white lines if necessary.
         let mut new_board = board.board.clone();
-        while new_board.len() < (self.altitude + self.get_height() + 3) as usize {
+        while new_board.len() < (self.position.y + self.get_height() + 3) as usize {
             new_board.push(vec![false; new_board[0].len()]);
         }
 
         for shape_line in 0..self.get_height() {
             for x_idx in 0..self.get_width() {
-                new_board[(self.altitude + shape_line) as usize][(x_idx + self.x_pos) as usize] =
-                new_board[(self.altitude + shape_line) as usize][(x_idx + self.x_pos) as usize] || 
+                let map_cell = self.position + Point2::new(x_idx, shape_line);
+                new_board[map_cell.y as usize][map_cell.x as usize] =
+                new_board[map_cell.y as usize][map_cell.x as usize] || 
                 self.shape[shape_line as usize][x_idx as usize];
             }
         }
@@ -97,14 +92,13 @@ impl TetrisBlock {
     }
 
 
-    fn collision_with_borders(&self, board_width: u32, direction : &Directions) -> bool {
-        (self.x_pos <= 0 && direction == &Directions::Left) ||
-        (self.x_pos >= board_width - self.get_width() && direction == &Directions::Right) || 
-        (self.altitude == 0 && direction == &Directions::Down)
+    fn collision_with_borders(&self, board_width: i32, direction : Dir4) -> bool {
+        let new_position = self.position + direction.delta_y_up();
+        new_position.x < 0 || new_position.x > board_width - self.get_width() || new_position.y < 0
     } 
 
     
-    fn collision_with_map(&self, board : &TetrisBoard, direction : &Directions) -> bool {
+    fn collision_with_map(&self, board : &TetrisBoard, direction : Dir4) -> bool {
 
         // Creating a copy of the block and moving it.
         let mut block_copy : TetrisBlock = self.clone();
@@ -112,15 +106,15 @@ impl TetrisBlock {
 
         // checking each line:
         for y_idx in 0..block_copy.get_height(){
-            let y_map = y_idx + block_copy.altitude; // Checking the row below
+            let y_map = y_idx + block_copy.position.y; // Checking the row below
 
-            if y_map >= board.board.len() as u32 {
+            if y_map >= board.board.len() as i32 {
                 continue;
             }
 
             for x_idx in 0..block_copy.get_width() {
 
-                let x_map = x_idx + block_copy.x_pos;
+                let x_map = x_idx + block_copy.position.x;
 
                 if board.board[y_map as usize][x_map as usize] && 
                     block_copy.shape[y_idx as usize][x_idx as usize] {
@@ -146,13 +140,13 @@ impl TetrisBlock {
 
 
     // Dimensional values
-    fn get_width(&self) -> u32 {
-        self.shape[0].len() as u32
+    fn get_width(&self) -> i32 {
+        self.shape[0].len() as i32
     }
 
 
-    fn get_height(&self) -> u32 {
-        self.shape.len() as u32
+    fn get_height(&self) -> i32 {
+        self.shape.len() as i32
     }
 }
 
@@ -169,8 +163,8 @@ impl TetrisBoard {
     }
 
 
-    fn get_width(&self) -> u32 {
-        self.board[0].len() as u32
+    fn get_width(&self) -> i32 {
+        self.board[0].len() as i32
     } 
 
 
@@ -215,11 +209,10 @@ impl TetrisBoard {
     }
 
 
-    fn add_block_till_bottom(&mut self, commands_vec : &Vec<Directions>) {
+    fn add_block_till_bottom(&mut self, commands_vec : &Vec<Dir4>) {
         let mut new_block = TetrisBlock::new(
             TetrisBlock::block_type_from_num(self.type_counter), 
-            self.get_height() + 3,
-            2 /* Always 2 */); 
+            Point2::new(2 /* Always 2 */, self.get_height() as i32 + 3)); 
 
         self.type_counter += 1;
             
@@ -227,12 +220,12 @@ impl TetrisBoard {
         loop {
 
             // Moving the block if possible.
-            new_block.try_move_block(&commands_vec[self.time_counter as usize % commands_vec.len()], self);
+            new_block.try_move_block(commands_vec[self.time_counter as usize % commands_vec.len()], self);
 
             self.time_counter += 1;
 
             // Making the block fall:
-            if let Some(new_map) = new_block.try_move_block(&Directions::Down, self) {
+            if let Some(new_map) = new_block.try_move_block(Dir4::Down, self) {
                 self.board = new_map;
                 break;
             }
@@ -241,19 +234,41 @@ impl TetrisBoard {
 }
 
 
+// The board is saved top row first, as it would be drawn.
+impl Snapshot for TetrisBoard {
+    const KIND : &'static str = "tetris_board";
 
+    fn write_snapshot(&self, writer : &mut SnapshotWriter) {
+        writer.value("time_counter", self.time_counter);
+        writer.value("type_counter", self.type_counter);
+        let rows : Vec<String> = self.board.iter().rev()
+            .map(|row| row.iter().map(|&val| if val {'#'} else {'.'}).collect())
+            .collect();
+        writer.grid("board", &rows);
+    }
 
-// Primary Function
-fn execute (input_path : String)  -> Option<(u32, u64)> {
+    fn read_snapshot(reader : &mut SnapshotReader) -> Result<TetrisBoard, SnapshotError> {
+        let time_counter = reader.value("time_counter")?;
+        let type_counter = reader.value("type_counter")?;
+        let rows = reader.grid("board")?;
+        if rows.is_empty() || rows.iter().any(|row| row.len() != rows[0].len() || row.contains(|c| c != '#' && c != '.')) {
+            return Err(reader.error("the board rows must have the same width and contain only '#' and '.'".to_string()));
+        }
+        let board = rows.iter().rev().map(|row| row.chars().map(|c| c == '#').collect()).collect();
+        Ok(TetrisBoard {board, time_counter, type_counter})
+    }
+}
+
+
+
+
+// Reading the jets of gas as left-right commands, all on one line.
+fn read_commands(input_path : &str) -> Vec<Dir4> {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
-    // Results variables:
-    let result_part_1 : u32;
-    let result_part_2 : u64;
-
     // First reading the input string - easy.
     let mut lines_vec = Vec::<String>::new();
 
@@ -267,26 +282,80 @@ fn execute (input_path : String)  -> Option<(u32, u64)> {
     assert!(lines_vec.len() == 1);
 
     // Converting in left-right commands:
-    let mut commands_vec = Vec::<Directions>::new();
+    let mut commands_vec = Vec::<Dir4>::new();
     for character in lines_vec[0].chars() {
         match character {
-            '>' => commands_vec.push(Directions::Right),
-            '<' => commands_vec.push(Directions::Left),
+            '>' => commands_vec.push(Dir4::Right),
+            '<' => commands_vec.push(Dir4::Left),
             _ => panic!("Wrong input character!"),
         }
     }
+    commands_vec
+}
+
+
+// The board of part 1 receiving one rock per step, for the stepper.
+struct TetrisStepper {
+    tetris_board : TetrisBoard,
+    commands_vec : Vec<Dir4>,
+    max_rocks : u32,
+}
+
+impl Steppable for TetrisStepper {
+    fn step(&mut self) -> bool {
+        if self.tetris_board.type_counter >= self.max_rocks {
+            return false;
+        }
+        self.tetris_board.add_block_till_bottom(&self.commands_vec);
+        true
+    }
+
+    fn step_number(&self) -> u64 {
+        self.tetris_board.type_counter as u64
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        vec![
+            ("type_counter".to_string(), self.tetris_board.type_counter.to_string()),
+            ("time_counter".to_string(), self.tetris_board.time_counter.to_string()),
+            ("command_index".to_string(), (self.tetris_board.time_counter as usize % self.commands_vec.len()).to_string()),
+            ("height".to_string(), self.tetris_board.get_height().to_string()),
+        ]
+    }
+
+    // The top of the tower, top row first.
+    fn draw(&self) -> String {
+        let height = self.tetris_board.get_height() as usize;
+        let rows : Vec<String> = self.tetris_board.board[..height].iter().rev().take(30)
+            .map(|row| format!("|{}|", row.iter().map(|&val| if val {'#'} else {'.'}).collect::<String>()))
+            .collect();
+        rows.join("\n")
+    }
+}
+
+
+// Primary Function
+fn execute (input_path : String, snapshots : &SnapshotOptions)  -> Option<(u32, u64)> {
+
+    // Results variables:
+    let result_part_1 : u32;
+    let result_part_2 : u64;
+
+    let commands_vec = read_commands(&input_path);
     println!("There are {} directional commands", commands_vec.len());
 
-    // Creating the tetris board: 
-    let mut tetris_board = TetrisBoard::new(7);
+    // Creating the tetris board, or restoring a saved one:
+    let mut tetris_board = snapshots.load_resume().unwrap_or(TetrisBoard::new(7));
 
-    // Iterating through the various turns:
+    // Iterating through the various turns, the step of the snapshots is the number of rocks:
     let max_rocks = 2022;
-    for _ in 0..max_rocks {
+    for _ in tetris_board.type_counter..max_rocks {
         // First adding a new stone
         tetris_board.add_block_till_bottom(&commands_vec);
+        snapshots.save_if_at(tetris_board.type_counter as u64, &tetris_board);
     }
     result_part_1 = tetris_board.get_height();
+    memory::report_part(1);
 
     // For part 2 I am expected to iterate 1E12 times, which doesn't sound very feasible.
     // I'd instead search for a periodicity of the input values, and when found just multiply 
@@ -345,6 +414,7 @@ fn execute (input_path : String)  -> Option<(u32, u64)> {
     }
 
     result_part_2 = total_height;
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -353,7 +423,18 @@ fn execute (input_path : String)  -> Option<(u32, u64)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 17!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // The simulation of part 1 can be saved with --snapshot <rocks> <file> and resumed with --resume <file>,
+    // or run interactively with --step.
+    let mut args = DayArgs::from_env();
+    let snapshots = SnapshotOptions::from_args(&mut args);
+    let step_mode = args.take_switch("--step");
+    let input_path = args.input_path("./data/input.txt");
+    if step_mode {
+        let mut tetris_stepper = TetrisStepper { tetris_board : TetrisBoard::new(7), commands_vec : read_commands(&input_path), max_rocks : 2022 };
+        return stepper::run_stepper(&mut tetris_stepper, io::stdin().lock(), &mut io::stdout());
+    }
+    let results = execute(input_path, &snapshots).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -366,15 +447,63 @@ fn main() -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::snapshot;
 
     // General Test
     #[test]
     fn global_test_part_1() {
-        assert_eq!(execute("./data/test.txt".to_string()).unwrap().0, 3068);
+        assert_eq!(execute("./data/test.txt".to_string(), &SnapshotOptions::default()).unwrap().0, 3068);
     }    
 
     #[test]
     fn global_test_part_2() {
-        assert_eq!(execute("./data/test.txt".to_string()).unwrap().1, 1514285714288);
+        assert_eq!(execute("./data/test.txt".to_string(), &SnapshotOptions::default()).unwrap().1, 1514285714288);
     }    
-}
\ No newline at end of file
+
+    // Snapshots
+    #[test]
+    fn test_snapshot_golden() {
+        let commands_vec : Vec<Dir4> = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>".chars()
+            .map(|c| if c == '>' {Dir4::Right} else {Dir4::Left})
+            .collect();
+        let mut tetris_board = TetrisBoard::new(7);
+        for _ in 0..10 {
+            tetris_board.add_block_till_bottom(&commands_vec);
+        }
+
+        let snapshot_string = snapshot::to_string(&tetris_board);
+        snapshot::check_golden(std::path::Path::new("./data/golden/tetris_10_rocks.snap"), &snapshot_string).unwrap();
+
+        let restored : TetrisBoard = snapshot::from_str(&snapshot_string).unwrap();
+        assert_eq!(restored.board, tetris_board.board);
+        assert_eq!(restored.time_counter, tetris_board.time_counter);
+        assert_eq!(snapshot::to_string(&restored), snapshot_string);
+    }
+
+    #[test]
+    fn test_snapshot_resume() {
+        let snapshot_path = std::env::temp_dir().join(format!("exercise_17_{}.snap", std::process::id()));
+        let save_options = SnapshotOptions { save_at : Some((500, snapshot_path.clone())), resume_from : None };
+        let full_results = execute("./data/test.txt".to_string(), &save_options).unwrap();
+
+        let resume_options = SnapshotOptions { save_at : None, resume_from : Some(snapshot_path.clone()) };
+        let resumed_results = execute("./data/test.txt".to_string(), &resume_options).unwrap();
+        std::fs::remove_file(snapshot_path).unwrap();
+        assert_eq!(resumed_results, full_results);
+    }
+
+    // Stepper
+    #[test]
+    fn test_tetris_stepper() {
+        let mut tetris_stepper = TetrisStepper { tetris_board : TetrisBoard::new(7), commands_vec : read_commands("./data/test.txt"), max_rocks : 2022 };
+        stepper::execute_command(&mut tetris_stepper, "run 10").unwrap();
+        assert_eq!(tetris_stepper.tetris_board.get_height(), 17);
+
+        stepper::execute_command(&mut tetris_stepper, "until time_counter >= 1000").unwrap();
+        assert!(tetris_stepper.tetris_board.time_counter >= 1000);
+        stepper::execute_command(&mut tetris_stepper, "run 5000").unwrap();
+        assert_eq!(tetris_stepper.step_number(), 2022);
+        assert_eq!(tetris_stepper.tetris_board.get_height(), 3068);
+        assert!(tetris_stepper.draw().starts_with("|"));
+    }
+}
@@ -3,11 +3,13 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
 use std::collections::HashSet;
 
-// Defining a simple 3D point, don't want to use complex stuff for this.
-const DIMENSIONS : usize= 3;
-type Point = [i32; DIMENSIONS];
+// Using the shared 3D point.
+use common::geometry::Point3;
+use common::memory;
+type Point = Point3;
 
 
 #[derive(Debug)]
@@ -21,7 +23,7 @@ impl VoxelSet {
 
 
     fn add_voxel(&mut self, pos : &Point) {
-        self.voxels.insert(pos.clone());
+        self.voxels.insert(*pos);
     }
 
 
@@ -38,9 +40,8 @@ impl VoxelSet {
 
     fn check_adjacent(&self, pos : &Point) -> usize {
         
-        let adj_coords = VoxelSet::get_adjacent_coords(pos);
         let mut adj_counter : usize = 0;
-        for elem in adj_coords {
+        for elem in pos.neighbours6() {
             if !self.voxels.contains(&elem) {
                 adj_counter += 1;
             }
@@ -48,26 +49,7 @@ impl VoxelSet {
         adj_counter
     } 
 
-    
-    fn get_adjacent_coords(pos : &Point) -> Vec<Point> {
-        
-        // Ugly approach, but that's how it is with tuples.
-        let mut all_coords = Vec::<Point>::new();
-        let mut temp_point = pos.clone();
-        for dim_idx in 0..pos.len() {
-
-            // Increasing by 1, then going to -1 then back to +0
-            temp_point[dim_idx] += 1;
-            all_coords.push(temp_point.clone());
-            temp_point[dim_idx] -= 2;
-            all_coords.push(temp_point.clone());
-            temp_point[dim_idx] += 1;
-        }
-        all_coords
-    }
-
-
-    fn find_cluster_around_lava(&self, pos : &Point, limits : &Vec::<(i32, i32)>) -> HashSet<Point> {
+    fn find_cluster_around_lava(&self, pos : &Point, limits : &(Point, Point)) -> HashSet<Point> {
 
         let mut previous_visited = HashSet::<Point>::new();
         self.find_cluster_iterative(pos, &mut previous_visited, limits);
@@ -78,7 +60,7 @@ impl VoxelSet {
     fn find_cluster_iterative(
         &self, pos : &Point,
         previous_visited : &mut HashSet<Point>, 
-        limits : &Vec::<(i32, i32)>) {
+        limits : &(Point, Point)) {
         
         // Check if already visited.
         if previous_visited.contains(pos) || self.voxels.contains(pos){
@@ -86,13 +68,11 @@ impl VoxelSet {
         }
 
         // Otherwise add to previous, and call function to all neighbours
-        previous_visited.insert(pos.clone());
-        for neighbour in VoxelSet::get_adjacent_coords(pos) {
-            if neighbour[0] < limits[0].0 || neighbour[0] > limits[0].1 ||
-                neighbour[1] < limits[1].0 || neighbour[1] > limits[1].1 ||
-                neighbour[2] < limits[2].0 || neighbour[2] > limits[2].1 {
-                    continue;
-                }
+        previous_visited.insert(*pos);
+        for neighbour in pos.neighbours6() {
+            if !neighbour.is_within_box(&limits.0, &limits.1) {
+                continue;
+            }
 
             self.find_cluster_iterative(&neighbour, previous_visited, limits);
         }
@@ -125,42 +105,39 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     // Converting lines in coordinates 
     let mut lava_space : VoxelSet = VoxelSet::new();
     for line in lines_vec {
-        lava_space.add_voxel(&line.split(",")
-        .map(|dim| {dim.parse::<i32>().unwrap()})
-        .collect::<Vec<i32>>()
-        .try_into()
-        .unwrap_or_else(|v: Vec<i32>| panic!("Expected a Vec of length {} but it was {}", 3, v.len())));
+        let coords : [i32; 3] = line.split(",")
+            .map(|dim| {dim.parse::<i32>().unwrap()})
+            .collect::<Vec<i32>>()
+            .try_into()
+            .unwrap_or_else(|v: Vec<i32>| panic!("Expected a Vec of length {} but it was {}", 3, v.len()));
+        lava_space.add_voxel(&Point::from(coords));
     }
 
     result_part_1 = lava_space.calculate_surface() as u32;
+    memory::report_part(1);
 
     // part 2 requires to find air pockets within the lava and remove them from the surface calculation.
     
     // Sampling random points and clustering until reaching the edge. 
-    // To that, finding the bounding box.
-    let mut limits = Vec::<(i32, i32)>::new();
-    for dim_idx in 0..DIMENSIONS {
-        limits.push((
-            lava_space.voxels.iter()
-            .min_by(|a, b| {a[dim_idx].cmp(&b[dim_idx])}).unwrap()[dim_idx] - 1,
-            lava_space.voxels.iter()
-            .max_by(|a, b| {a[dim_idx].cmp(&b[dim_idx])}).unwrap()[dim_idx] + 1));
+    // To that, finding the bounding box, with one voxel of margin.
+    let mut limits = (Point::splat(i32::MAX), Point::splat(i32::MIN));
+    for voxel in &lava_space.voxels {
+        limits = (limits.0.axis_min(voxel), limits.1.axis_max(voxel));
     }
+    limits = (limits.0 - Point::splat(1), limits.1 + Point::splat(1));
 
     // Exploring the bounded space starting from the bottom-left point (which is outside)
-    // Till this point dimensions are parametrical. For the iteration i set it fixed, but a better 
-    // solution could be found.let zero_point: Point = vec!{limits[0].0, limits[1].0, limits[2].0}.try_into().unwrap();
-    let zero_point: Point = vec!{limits[0].0, limits[1].0, limits[2].0}.try_into().unwrap();
+    let zero_point: Point = limits.0;
     println!("Finding clusters, starting from {:?}.", zero_point);
     let outside_voxels = lava_space.find_cluster_around_lava(&zero_point, &limits).clone();
     println!("outside voxels are {}.", outside_voxels.len());
 
     // Finding all points: 
     let mut lava_space_filled : VoxelSet = VoxelSet::new();
-    for x in limits[0].0..limits[0].1 + 1 {
-        for y in limits[1].0..limits[1].1 + 1 {
-            for z in limits[2].0..limits[2].1 + 1 {
-                let temp_point: Point = vec!{x, y, z}.try_into().unwrap();
+    for x in limits.0.x..limits.1.x + 1 {
+        for y in limits.0.y..limits.1.y + 1 {
+            for z in limits.0.z..limits.1.z + 1 {
+                let temp_point = Point::new(x, y, z);
                 if !outside_voxels.contains(&temp_point) {
                     lava_space_filled.add_voxel(&temp_point);
                 }
@@ -170,6 +147,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     println!("reciprocal voxels are {}.", lava_space_filled.voxels.len());
     result_part_2 = lava_space_filled.calculate_surface() as u32;
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -178,7 +156,9 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 18!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
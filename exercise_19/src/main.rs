@@ -3,10 +3,14 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
 use regex::Regex;
 use std::collections::HashMap;
 use std::cmp::max;
 
+// Memory accounting
+use common::memory;
+
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum ResourceType {
@@ -320,6 +324,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     }
 
     result_part_1 = cumulative_result;
+    memory::report_part(1);
 
     // Part 2 is with 32 iterations, but only 3 blueprints.
     let mut cumulative_result = 1;
@@ -332,6 +337,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
         }
     }
     result_part_2 = cumulative_result;
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -339,7 +345,9 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 19!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -4,106 +4,645 @@
 // 3 for Scissor
 // 0 for losing
 // 3 for draw
-// 6 for winning 
+// 6 for winning
+//
+// The game is generalized to any odd number of hands in a cycle, where each hand
+// beats the half of the others that precede it (Rock Paper Scissors Spock Lizard and so on).
 
 // For reading/parsing
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, prelude::*, BufReader};
 
-#[derive(PartialEq)]
-#[derive(Clone)]
-#[derive(Copy)]
-enum RockPaperScissors {
-    R = 0,
-    P = 1,
-    S = 2,
+// Utility
+use std::cmp::Reverse;
+use common::cli::DayArgs;
+use common::memory;
+use common::top_k::top_k;
+
+// A hand is its position in the cycle of the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Hand(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+
+// The definition of a game, with the symbols of the two columns of the guide.
+#[derive(Clone, Debug)]
+struct HandGame {
+    hand_names : Vec<String>,
+    hand_values : Vec<i32>,
+    outcome_points : [i32; 3], // Loss, draw, win.
+    opponent_symbols : Vec<String>,
+    guide_symbols : Vec<String>,
 }
-impl RockPaperScissors {
-    fn from_i32(value: i32) -> RockPaperScissors {
-        match value {
-            0 => RockPaperScissors::R,
-            1 => RockPaperScissors::P,
-            2 => RockPaperScissors::S,
-            _ => panic!("Unknown value: {}", value),
+
+impl HandGame {
+    fn new(
+        hand_names : Vec<String>,
+        hand_values : Vec<i32>,
+        outcome_points : [i32; 3],
+        opponent_symbols : Vec<String>,
+        guide_symbols : Vec<String>) -> Result<HandGame, String> {
+
+        let hands_number = hand_names.len();
+        if hands_number < 3 || hands_number.is_multiple_of(2) {
+            return Err(format!("a cyclic game needs an odd number of hands (at least 3), not {}", hands_number));
+        }
+        for (what, length) in [("values", hand_values.len()), ("opponent symbols", opponent_symbols.len()), ("guide symbols", guide_symbols.len())] {
+            if length != hands_number {
+                return Err(format!("{} hands but {} {}", hands_number, length, what));
+            }
+        }
+        for symbols in [&opponent_symbols, &guide_symbols] {
+            if let Some(symbol) = symbols.iter().enumerate().find(|(idx, symbol)| symbols[..*idx].contains(symbol)).map(|(_, symbol)| symbol) {
+                return Err(format!("the symbol {} is used twice", symbol));
+            }
+        }
+        Ok(HandGame { hand_names, hand_values, outcome_points, opponent_symbols, guide_symbols })
+    }
+
+    // The original game.
+    fn rock_paper_scissors() -> HandGame {
+        HandGame::parse("hands = Rock Paper Scissors\nvalues = 1 2 3\npoints = 0 3 6\nopponent = A B C\nguide = X Y Z\n").unwrap()
+    }
+
+    // A game definition, one "key = values" per line:
+    //   hands = Rock Spock Paper Lizard Scissors   (in the order of the cycle)
+    //   values = 1 2 3 4 5
+    //   points = 0 3 6                             (loss, draw, win)
+    //   opponent = A B C D E
+    //   guide = V W X Y Z
+    fn parse(text : &str) -> Result<HandGame, String> {
+        let mut fields = std::collections::HashMap::<&str, Vec<String>>::new();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let (key, values) = line.split_once('=').ok_or(format!("expected 'key = values', got '{}'", line))?;
+            fields.insert(key.trim(), values.split_whitespace().map(str::to_string).collect());
+        }
+        let mut take = |key : &str| fields.remove(key).ok_or(format!("missing '{}'", key));
+        let parse_numbers = |values : Vec<String>| -> Result<Vec<i32>, String> {
+            values.iter().map(|value| value.parse::<i32>().map_err(|_| format!("'{}' is not a number", value))).collect()
+        };
+
+        let hand_names = take("hands")?;
+        let hand_values = parse_numbers(take("values")?)?;
+        let outcome_points : [i32; 3] = parse_numbers(take("points")?)?.try_into()
+            .map_err(|_| "the points are three numbers: loss, draw and win".to_string())?;
+        let game = HandGame::new(hand_names, hand_values, outcome_points, take("opponent")?, take("guide")?)?;
+        match fields.keys().next() {
+            Some(unknown) => Err(format!("unknown key '{}'", unknown)),
+            None => Ok(game),
+        }
+    }
+
+    fn hands_number(&self) -> usize {
+        self.hand_names.len()
+    }
+
+    // Each hand beats the half of the cycle before it and loses against the half after it.
+    fn outcome(&self, opponent : Hand, mine : Hand) -> Outcome {
+        let distance = (mine.0 + self.hands_number() - opponent.0) % self.hands_number();
+        if distance == 0 {
+            Outcome::Draw
+        }
+        else if distance <= self.hands_number() / 2 {
+            Outcome::Win
         }
+        else {
+            Outcome::Loss
+        }
+    }
+
+    fn get_score(&self, opponent : Hand, mine : Hand) -> i32 {
+        self.hand_values[mine.0] + self.outcome_points[self.outcome(opponent, mine) as usize]
+    }
+
+    fn parse_round(&self, line : &str) -> Result<(Hand, usize), String> {
+        let mut symbols = line.split_whitespace();
+        let (Some(opponent), Some(guide), None) = (symbols.next(), symbols.next(), symbols.next()) else {
+            return Err(format!("expected two symbols, got '{}'", line));
+        };
+        let opponent_idx = self.opponent_symbols.iter().position(|symbol| symbol == opponent)
+            .ok_or(format!("unknown opponent symbol '{}'", opponent))?;
+        let guide_idx = self.guide_symbols.iter().position(|symbol| symbol == guide)
+            .ok_or(format!("unknown guide symbol '{}'", guide))?;
+        Ok((Hand(opponent_idx), guide_idx))
+    }
+
+    // Part 1: the guide symbols are the hands to play, in the same order.
+    fn hand_part_1(&self, guide_idx : usize) -> Hand {
+        Hand(guide_idx)
+    }
+
+    // Part 2: the guide symbols are how far to play from the opponent along the cycle,
+    // the middle symbol is a draw, the ones before lose and the ones after win.
+    // With three hands it's lose, draw and win.
+    fn hand_part_2(&self, opponent : Hand, guide_idx : usize) -> Hand {
+        Hand((opponent.0 + guide_idx + self.hands_number() - self.hands_number() / 2) % self.hands_number())
     }
 }
 
-fn get_score(input: RockPaperScissors, output: RockPaperScissors) -> i32{
 
-    // Calculating the "played hand" value:
-    let mut score = 0;
-    score += (output as i32) + 1;
+// The opponent hands and the guide symbols of each round, interpreted later.
+fn read_strategy(reader : impl BufRead, game : &HandGame) -> Vec<(Hand, usize)> {
+    let mut strategy_vec = Vec::<(Hand, usize)>::new();
+    for line in reader.lines().map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        strategy_vec.push(game.parse_round(&line).unwrap_or_else(|message| panic!("Wrong input line: {}", message)));
+    }
+    strategy_vec
+}
 
-    // To confront hands, it wins if output = (input + 1) % 3
-    if input == output {
-        score += 3;
+
+// What each round is worth with the two interpretations of the guide.
+fn breakdown(game : &HandGame, strategy_vec : &[(Hand, usize)]) -> String {
+    let mut table = format!("{:>6} {:>10} {:>6} {:>12} {:>7} {:>12} {:>7}\n", "round", "opponent", "guide", "part 1 hand", "score", "part 2 hand", "score");
+    let mut totals = (0, 0);
+    for (round, &(opponent, guide_idx)) in strategy_vec.iter().enumerate() {
+        let hand_1 = game.hand_part_1(guide_idx);
+        let hand_2 = game.hand_part_2(opponent, guide_idx);
+        let scores = (game.get_score(opponent, hand_1), game.get_score(opponent, hand_2));
+        totals = (totals.0 + scores.0, totals.1 + scores.1);
+        table += &format!("{:>6} {:>10} {:>6} {:>12} {:>7} {:>12} {:>7}\n",
+            round + 1, game.hand_names[opponent.0], game.guide_symbols[guide_idx],
+            game.hand_names[hand_1.0], scores.0, game.hand_names[hand_2.0], scores.1);
     }
-    else if output as i32 == ((input as i32) + 1) % 3 {
-        score += 6;
+    table += &format!("{:>6} {:>10} {:>6} {:>12} {:>7} {:>12} {:>7}\n", "total", "", "", "", totals.0, "", totals.1);
+    table
+}
+
+
+// A guide scoring exactly the target against the opponent hands, read with the part 1
+// interpretation. The reachable scores are computed from the last round backwards,
+// then each round takes the first hand that keeps the rest of the target reachable.
+// Each round only keeps the scores between the lowest and the highest the rounds from
+// there can make, so the table stays within the score range and never above the target.
+fn guide_for_target(game : &HandGame, opponents : &[Hand], target : i32) -> Result<Vec<Hand>, String> {
+    let round_scores : Vec<Vec<i32>> = opponents.iter()
+        .map(|&opponent| (0..game.hands_number()).map(|mine| game.get_score(opponent, Hand(mine))).collect())
+        .collect();
+    if round_scores.iter().flatten().any(|&score| score < 0) {
+        return Err("the generator needs games without negative scores".to_string());
+    }
+    let lowest : i32 = round_scores.iter().map(|scores| scores.iter().min().unwrap()).sum();
+    let highest : i32 = round_scores.iter().map(|scores| scores.iter().max().unwrap()).sum();
+    if target < lowest || target > highest {
+        return Err(format!("the scores go from {} to {}, not {}", lowest, highest, target));
+    }
+
+    // The range of the scores of the rounds from each one on, capped by the target.
+    let mut ranges = vec![(0, 0); opponents.len() + 1];
+    for round in (0..opponents.len()).rev() {
+        let (low, high) = ranges[round + 1];
+        ranges[round] = (low + round_scores[round].iter().min().unwrap(), (high + round_scores[round].iter().max().unwrap()).min(target));
     }
 
-    score
+    // reachable[round][score - low]: the rounds from this one on can make exactly the score.
+    let mut reachable = vec![Vec::<bool>::new(); opponents.len() + 1];
+    reachable[opponents.len()] = vec![true];
+    let is_reachable = |reachable : &[Vec<bool>], round : usize, total : i32| {
+        let (low, high) = ranges[round];
+        low <= total && total <= high && reachable[round][(total - low) as usize]
+    };
+    for round in (0..opponents.len()).rev() {
+        let (low, high) = ranges[round];
+        let row : Vec<bool> = (low..=high)
+            .map(|total| round_scores[round].iter().any(|&score| is_reachable(&reachable, round + 1, total - score)))
+            .collect();
+        reachable[round] = row;
+    }
+    if !is_reachable(&reachable, 0, target) {
+        return Err(format!("no guide scores exactly {}", target));
+    }
+
+    let mut remaining = target;
+    let mut hands = Vec::<Hand>::with_capacity(opponents.len());
+    for (round, scores) in round_scores.iter().enumerate() {
+        let mine = (0..scores.len())
+            .find(|&mine| is_reachable(&reachable, round + 1, remaining - scores[mine]))
+            .unwrap();
+        remaining -= scores[mine];
+        hands.push(Hand(mine));
+    }
+    Ok(hands)
 }
 
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 2!");
 
-    // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
-    let reader = BufReader::new(file);
+// The best guide changing at most the given number of rounds of the part 1 interpretation:
+// the rounds that gain the most by playing their best hand instead. Returns the hands and the score.
+fn improve_guide(game : &HandGame, strategy_vec : &[(Hand, usize)], changes : usize) -> (Vec<Hand>, i32) {
+    let mut hands : Vec<Hand> = strategy_vec.iter().map(|&(_, guide_idx)| game.hand_part_1(guide_idx)).collect();
+    let best_hands : Vec<Hand> = strategy_vec.iter()
+        .map(|&(opponent, _)| (0..game.hands_number()).map(Hand).max_by_key(|&mine| game.get_score(opponent, mine)).unwrap())
+        .collect();
+    let gains = strategy_vec.iter().zip(&hands).zip(&best_hands).enumerate()
+        .map(|(round, ((&(opponent, _), &current), &best))| (game.get_score(opponent, best) - game.get_score(opponent, current), Reverse(round)))
+        .filter(|&(gain, _)| gain > 0);
+    for (_, Reverse(round)) in top_k(gains, changes) {
+        hands[round] = best_hands[round];
+    }
+    let score = strategy_vec.iter().zip(&hands).map(|(&(opponent, _), &mine)| game.get_score(opponent, mine)).sum();
+    (hands, score)
+}
+
+
+// The guide in the input format, with the guide symbols of the hands (the part 1 interpretation).
+fn format_guide(game : &HandGame, opponents : &[Hand], hands : &[Hand]) -> String {
+    opponents.iter().zip(hands)
+        .map(|(opponent, mine)| format!("{} {}\n", game.opponent_symbols[opponent.0], game.guide_symbols[mine.0]))
+        .collect()
+}
+
+
+// A small xorshift generator, seeded so that the tournaments can be replayed.
+struct Random(u64);
+
+impl Random {
+    fn new(seed : u64) -> Random {
+        // The state must not be zero.
+        Random(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound : usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+
+// How a player of the tournament picks its hands.
+enum Strategy {
+    // The same hands every match, repeated when there are more rounds than hands.
+    Fixed(Vec<Hand>),
+    Random,
+    // The hand beating the one the opponent played the most so far in the match.
+    FrequencyCounter,
+}
+
+impl Strategy {
+    // The fixed hands need at least one hand to repeat.
+    fn fixed(hands : Vec<Hand>) -> Result<Strategy, String> {
+        if hands.is_empty() {
+            return Err("the guide has no round".to_string());
+        }
+        Ok(Strategy::Fixed(hands))
+    }
+
+    fn choose(&self, game : &HandGame, round : usize, opponent_counts : &[usize], random : &mut Random) -> Hand {
+        match self {
+            Strategy::Fixed(hands) => hands[round % hands.len()],
+            Strategy::Random => Hand(random.below(game.hands_number())),
+            Strategy::FrequencyCounter => {
+                // The first of the most played, then the next hand in the cycle wins against it.
+                let favourite = (0..game.hands_number()).rev().max_by_key(|&hand| opponent_counts[hand]).unwrap();
+                Hand((favourite + 1) % game.hands_number())
+            },
+        }
+    }
+}
 
-    // Reading in two vectors, then using the "zip" functionality to work along them
-    let mut strategy_vec = Vec::<(RockPaperScissors, RockPaperScissors)>::new();
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-
-            // Parsing input
-            let input : RockPaperScissors;
-            let mut couple = line.split(" ");
-            match couple.next().unwrap() {
-                "A" => input = RockPaperScissors::R,
-                "B" => input = RockPaperScissors::P,
-                "C" => input = RockPaperScissors::S,
-                _ => panic!("Wrong input character!"),
+
+struct Player {
+    name : String,
+    strategy : Strategy,
+}
+
+impl Player {
+    fn new(name : &str, strategy : Strategy) -> Player {
+        Player { name : name.to_string(), strategy }
+    }
+}
+
+
+// The players made from a guide: the guide read as hands (part 1), the guide read as
+// outcomes against the hands the elf is expected to play (part 2), the elf playing
+// those hands, and the two adaptive players.
+fn guide_players(game : &HandGame, strategy_vec : &[(Hand, usize)]) -> Result<Vec<Player>, String> {
+    Ok(vec![
+        Player::new("guide", Strategy::fixed(strategy_vec.iter().map(|&(_, guide_idx)| game.hand_part_1(guide_idx)).collect())?),
+        Player::new("outcomes", Strategy::fixed(strategy_vec.iter().map(|&(opponent, guide_idx)| game.hand_part_2(opponent, guide_idx)).collect())?),
+        Player::new("elf", Strategy::fixed(strategy_vec.iter().map(|&(opponent, _)| opponent).collect())?),
+        Player::new("random", Strategy::Random),
+        Player::new("frequency", Strategy::FrequencyCounter),
+    ])
+}
+
+
+// The scores of the two players of a match, and how many rounds each one won.
+fn play_match(game : &HandGame, players : [&Player; 2], rounds : usize, random : &mut Random) -> ([i32; 2], [usize; 2]) {
+    let mut scores = [0, 0];
+    let mut wins = [0, 0];
+    let mut counts = [vec![0; game.hands_number()], vec![0; game.hands_number()]];
+    for round in 0..rounds {
+        let hands = [
+            players[0].strategy.choose(game, round, &counts[1], random),
+            players[1].strategy.choose(game, round, &counts[0], random),
+        ];
+        for side in 0..2 {
+            scores[side] += game.get_score(hands[1 - side], hands[side]);
+            if game.outcome(hands[1 - side], hands[side]) == Outcome::Win {
+                wins[side] += 1;
             }
+            counts[side][hands[side].0] += 1;
+        }
+    }
+    (scores, wins)
+}
 
-            // Parsing Output
-            let output : RockPaperScissors;
-            match couple.next().unwrap() {
-                "X" => output = RockPaperScissors::R,
-                "Y" => output = RockPaperScissors::P,
-                "Z" => output = RockPaperScissors::S,
-                _ => panic!("Wrong input character!"),
+
+// Every player against every other one, the scores are the ones of the row player.
+struct TournamentTable {
+    names : Vec<String>,
+    rounds : usize,
+    scores : Vec<Vec<i32>>,
+    wins : Vec<usize>,
+    losses : Vec<usize>,
+}
+
+impl TournamentTable {
+    fn total(&self, player : usize) -> i32 {
+        self.scores[player].iter().sum()
+    }
+
+    fn draw(&self) -> String {
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max(7);
+        let mut table = format!("{:>width$}", "", width = width);
+        for name in &self.names {
+            table += &format!(" {:>width$}", name, width = width);
+        }
+        table += &format!(" {:>width$} {:>7} {:>7} {:>7}\n", "total", "won", "drawn", "lost", width = width);
+        let played = self.rounds * (self.names.len() - 1);
+        for (player, name) in self.names.iter().enumerate() {
+            table += &format!("{:>width$}", name, width = width);
+            for (opponent, score) in self.scores[player].iter().enumerate() {
+                if opponent == player {
+                    table += &format!(" {:>width$}", "-", width = width);
+                }
+                else {
+                    table += &format!(" {:>width$}", score, width = width);
+                }
             }
-        
-            strategy_vec.push((input, output));
+            let drawn = played - self.wins[player] - self.losses[player];
+            table += &format!(" {:>width$} {:>7} {:>7} {:>7}\n", self.total(player), self.wins[player], drawn, self.losses[player], width = width);
         }
+        table
     }
+}
+
+
+// A round-robin of matches of the given number of rounds, replayable with the same seed.
+fn tournament(game : &HandGame, players : &[Player], rounds : usize, seed : u64) -> TournamentTable {
+    let mut random = Random::new(seed);
+    let mut table = TournamentTable {
+        names : players.iter().map(|player| player.name.clone()).collect(),
+        rounds,
+        scores : vec![vec![0; players.len()]; players.len()],
+        wins : vec![0; players.len()],
+        losses : vec![0; players.len()],
+    };
+    for first in 0..players.len() {
+        for second in first + 1..players.len() {
+            let (scores, wins) = play_match(game, [&players[first], &players[second]], rounds, &mut random);
+            table.scores[first][second] = scores[0];
+            table.scores[second][first] = scores[1];
+            table.wins[first] += wins[0];
+            table.wins[second] += wins[1];
+            table.losses[first] += wins[1];
+            table.losses[second] += wins[0];
+        }
+    }
+    table
+}
+
+
+// Primary Function
+fn execute (input_path : String, game : &HandGame)  -> Option<(i32, i32)> {
+
+    // Handling the reading/parsing
+    let file = File::open(input_path).unwrap();
+    let reader = BufReader::new(file);
+
+    // Reading the opponent hands and the symbols of the guide, interpreted later.
+    let strategy_vec = read_strategy(reader, game);
 
     // Calculating the total values:
     let mut total_score = 0;
     for element in &strategy_vec {
-        total_score += get_score(element.0, element.1);
+        total_score += game.get_score(element.0, game.hand_part_1(element.1));
     }
 
     // Solution of PART 1
     println!("The maximum score of the {} moves would be {}.", strategy_vec.len(), total_score);
+    let result_part_1 = total_score;
+    memory::report_part(1);
 
-    // For PART 2 i have to reinterpret the second part of the vector:
-    // R for lose
-    // P for draw
-    // S for win
+    // For PART 2 i have to reinterpret the second part of the vector as the outcomes.
     let mut total_score = 0;
     for element in &strategy_vec {
-        // To get the element that makes you lose, you do -1 mod 3. Same to win (+1 mod 3).
-        // The offset is basically the int value of the enum -1 (or +2 since it's mod).
-        let new_move = ((element.0 as i32) + ((element.1 as i32) + 2)) % 3;
-        total_score += get_score(element.0, RockPaperScissors::from_i32(new_move));
+        total_score += game.get_score(element.0, game.hand_part_2(element.0, element.1));
+    }
+
+    let result_part_2 = total_score;
+
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
+
+
+// Main
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 2!");
+
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // Another game can be played with --game <definition file>, see HandGame::parse.
+    // With --breakdown the score of each round is listed for both parts. A new guide
+    // for the same opponent is saved with --target <score> <file> (scoring exactly the
+    // target) or --improve <K> <file> (the best score changing at most K rounds).
+    // With --tournament <rounds> <seed> the players made from the guide play each other.
+    let mut args = DayArgs::from_env();
+    let game = match args.take_option("--game", 1) {
+        Some(values) => HandGame::parse(&fs::read_to_string(&values[0])?)
+            .unwrap_or_else(|message| panic!("Wrong game definition in {}: {}", values[0], message)),
+        None => HandGame::rock_paper_scissors(),
+    };
+    let breakdown_mode = args.take_switch("--breakdown");
+    let target_option = args.take_option("--target", 2);
+    let improve_option = args.take_option("--improve", 2);
+    let tournament_option = args.take_option("--tournament", 2);
+    let input_path = args.input_path("./data/input.txt");
+    if breakdown_mode || target_option.is_some() || improve_option.is_some() || tournament_option.is_some() {
+        let strategy_vec = read_strategy(BufReader::new(File::open(&input_path)?), &game);
+        let opponents : Vec<Hand> = strategy_vec.iter().map(|&(opponent, _)| opponent).collect();
+        if breakdown_mode {
+            print!("{}", breakdown(&game, &strategy_vec));
+        }
+        if let Some(values) = target_option {
+            let target = values[0].parse::<i32>().expect("--target expects a score");
+            match guide_for_target(&game, &opponents, target) {
+                Ok(hands) => {
+                    fs::write(&values[1], format_guide(&game, &opponents, &hands))?;
+                    println!("Saved a guide scoring {} in {}.", target, values[1]);
+                },
+                Err(message) => println!("Cannot make a guide scoring {}: {}.", target, message),
+            }
+        }
+        if let Some(values) = improve_option {
+            let changes = values[0].parse::<usize>().expect("--improve expects a number of rounds");
+            let (hands, score) = improve_guide(&game, &strategy_vec, changes);
+            fs::write(&values[1], format_guide(&game, &opponents, &hands))?;
+            println!("Saved a guide scoring {} with at most {} changes in {}.", score, changes, values[1]);
+        }
+        if let Some(values) = tournament_option {
+            let rounds = values[0].parse::<usize>().expect("--tournament expects a number of rounds");
+            let seed = values[1].parse::<u64>().expect("--tournament expects a seed");
+            match guide_players(&game, &strategy_vec) {
+                Ok(players) => print!("{}", tournament(&game, &players, rounds, seed).draw()),
+                Err(message) => println!("Cannot play the tournament: {}.", message),
+            }
+        }
     }
+    let results = execute(input_path, &game).unwrap();
 
-    println!("The score for Part 2 would be {}.", total_score);
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // General Test
+    #[test]
+    fn global_test() {
+        assert_eq!(execute("./data/test.txt".to_string(), &HandGame::rock_paper_scissors()), Some((15, 12)));
+    }
+
+    #[test]
+    fn test_rock_paper_scissors() {
+        let game = HandGame::rock_paper_scissors();
+        assert_eq!(game.outcome(Hand(0), Hand(1)), Outcome::Win);
+        assert_eq!(game.outcome(Hand(0), Hand(2)), Outcome::Loss);
+        assert_eq!(game.outcome(Hand(2), Hand(0)), Outcome::Win);
+        assert_eq!(game.get_score(Hand(1), Hand(1)), 5);
+        assert_eq!(game.hand_part_2(Hand(0), 0), Hand(2));
+        assert_eq!(game.hand_part_2(Hand(2), 2), Hand(0));
+    }
+
+    #[test]
+    fn test_spock_lizard() {
+        let game = HandGame::parse(&fs::read_to_string("./data/rpsls.txt").unwrap()).unwrap();
+        let spock = Hand(1);
+        let lizard = Hand(3);
+        assert_eq!(game.outcome(spock, lizard), Outcome::Win);
+        assert_eq!(game.outcome(Hand(0), spock), Outcome::Win);
+        assert_eq!(game.outcome(Hand(4), spock), Outcome::Win);
+        assert_eq!(game.outcome(Hand(2), spock), Outcome::Loss);
+
+        // Every hand wins against two and loses against two.
+        for mine in 0..5 {
+            let wins = (0..5).filter(|&opponent| game.outcome(Hand(opponent), Hand(mine)) == Outcome::Win).count();
+            assert_eq!(wins, 2);
+        }
+
+        // "V" plays the hand two steps before the opponent, "Z" two steps after.
+        assert_eq!(game.hand_part_2(Hand(0), 0), Hand(3));
+        assert_eq!(game.hand_part_2(Hand(0), 4), Hand(2));
+        assert_eq!(execute("./data/test_rpsls.txt".to_string(), &game), Some((22, 22)));
+    }
+
+    #[test]
+    fn test_game_errors() {
+        assert!(HandGame::parse("hands = A B\nvalues = 1 2\npoints = 0 3 6\nopponent = A B\nguide = X Y").is_err());
+        assert!(HandGame::parse("hands = A B C\nvalues = 1 2\npoints = 0 3 6\nopponent = A B C\nguide = X Y Z").is_err());
+        assert!(HandGame::parse("hands = A B C\nvalues = 1 2 3\npoints = 0 6\nopponent = A B C\nguide = X Y Z").is_err());
+        assert!(HandGame::parse("hands = A B C\nvalues = 1 2 3\npoints = 0 3 6\nopponent = A B A\nguide = X Y Z").is_err());
+        assert!(HandGame::parse("hands = A B C\nvalues = 1 2 3\npoints = 0 3 6\nopponent = A B C\nguide = X Y Z\nrounds = 3").is_err());
+        assert!(HandGame::rock_paper_scissors().parse_round("A Q").is_err());
+        assert!(HandGame::rock_paper_scissors().parse_round("A").is_err());
+    }
+
+    #[test]
+    fn test_guide_generator() {
+        let game = HandGame::rock_paper_scissors();
+        let strategy_vec = read_strategy(BufReader::new(File::open("./data/test.txt").unwrap()), &game);
+        let opponents : Vec<Hand> = strategy_vec.iter().map(|&(opponent, _)| opponent).collect();
+        let score_of = |hands : &[Hand]| opponents.iter().zip(hands).map(|(&opponent, &mine)| game.get_score(opponent, mine)).sum::<i32>();
+
+        // The rounds score {3, 4, 8}, {1, 5, 9} and {2, 6, 7}.
+        let reachable = [6, 7, 10, 11, 12, 14, 15, 16, 18, 19, 20, 23, 24];
+        for target in 0..30 {
+            let Ok(hands) = guide_for_target(&game, &opponents, target) else {
+                assert!(!reachable.contains(&target));
+                continue;
+            };
+            assert!(reachable.contains(&target));
+            assert_eq!(score_of(&hands), target);
+
+            // The generated guide reads back as the same rounds.
+            let guide = format_guide(&game, &opponents, &hands);
+            assert_eq!(read_strategy(guide.as_bytes(), &game), opponents.iter().zip(&hands).map(|(&opponent, mine)| (opponent, mine.0)).collect::<Vec<_>>());
+        }
+        assert!(guide_for_target(&game, &[], 0).unwrap().is_empty());
+        assert_eq!(guide_for_target(&game, &opponents, i32::MAX), Err(format!("the scores go from 6 to 24, not {}", i32::MAX)));
+
+        // The guide scores 8 + 1 + 6, the second and third rounds could gain 8 and 1.
+        assert_eq!(improve_guide(&game, &strategy_vec, 0), (vec![Hand(1), Hand(0), Hand(2)], 15));
+        assert_eq!(improve_guide(&game, &strategy_vec, 1), (vec![Hand(1), Hand(2), Hand(2)], 23));
+        assert_eq!(improve_guide(&game, &strategy_vec, 5), (vec![Hand(1), Hand(2), Hand(0)], 24));
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let game = HandGame::rock_paper_scissors();
+        let strategy_vec = read_strategy(BufReader::new(File::open("./data/test.txt").unwrap()), &game);
+        let table = breakdown(&game, &strategy_vec);
+        let lines : Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1].split_whitespace().collect::<Vec<_>>(), ["1", "Rock", "Y", "Paper", "8", "Rock", "4"]);
+        assert_eq!(lines[4].split_whitespace().collect::<Vec<_>>(), ["total", "15", "12"]);
+    }
+
+    #[test]
+    fn test_tournament() {
+        let game = HandGame::rock_paper_scissors();
+        let strategy_vec = read_strategy(BufReader::new(File::open("./data/test.txt").unwrap()), &game);
+        let players = guide_players(&game, &strategy_vec).unwrap();
+
+        // Against the elf, the two readings of the guide score the two parts.
+        let table = tournament(&game, &players, strategy_vec.len(), 7);
+        assert_eq!(table.scores[0][2], 15);
+        assert_eq!(table.scores[1][2], 12);
+
+        // The same seed plays the same tournament.
+        let table = tournament(&game, &players, 100, 7);
+        assert_eq!(table.draw(), tournament(&game, &players, 100, 7).draw());
+        assert_eq!(table.wins.iter().sum::<usize>(), table.losses.iter().sum::<usize>());
+
+        // Against a single hand, the frequency counter wins every round but the first,
+        // where it expects Rock.
+        let players = [Player::new("scissors", Strategy::fixed(vec![Hand(2)]).unwrap()), Player::new("frequency", Strategy::FrequencyCounter)];
+        let table = tournament(&game, &players, 10, 1);
+        assert_eq!(table.wins, vec![1, 9]);
+        assert_eq!(table.scores[1][0], 2 + 9 * (1 + 6));
+        assert!(table.draw().lines().nth(2).unwrap().contains("frequency"));
+
+        // An empty guide has no hand to repeat.
+        assert!(guide_players(&game, &[]).is_err());
+    }
+}
@@ -3,6 +3,10 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
+
+// Memory accounting
+use common::memory;
 
 // Other useful includes:
 use std::fmt::Debug;
@@ -280,6 +284,7 @@ fn execute (input_path : String)  -> Option<(u32, u64)> {
         (looped_list.get_element_at_index(1000 + zero_index).unwrap().value + 
         looped_list.get_element_at_index(2000 + zero_index).unwrap().value + 
         looped_list.get_element_at_index(3000 + zero_index).unwrap().value) as u32;
+    memory::report_part(1);
 
     // The shift to be applied now has to be done ten times, but keeping the original logic.
     // This requires modifying the "apply shift" logic a bit, with a degenerate case of n=1
@@ -297,6 +302,7 @@ fn execute (input_path : String)  -> Option<(u32, u64)> {
         looped_list.get_element_at_index(2000 + zero_index).unwrap().value + 
         looped_list.get_element_at_index(3000 + zero_index).unwrap().value) as u64;
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -305,7 +311,9 @@ fn execute (input_path : String)  -> Option<(u32, u64)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 20!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
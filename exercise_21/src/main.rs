@@ -6,6 +6,10 @@
 use std::fs::File;
 use std::collections::HashMap;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
+
+// Memory accounting
+use common::memory;
 
 // utility
 
@@ -254,12 +258,15 @@ fn execute (input_path : String)  -> Option<(i64, i64)> {
         panic!("Part 1 failed!");
     }
 
+    memory::report_part(1);
+
     // For part 2 first solving everything that is not "contamined" by the human
     println!("Starting part 2...");
     let mut statements_map_part_2= statements_map.clone();
     simplify_statements_map(&mut statements_map_part_2, true /* Using humn */);
     result_part_2 = solve_with_humn(&statements_map_part_2);
     println!("Part 2 completed.");
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -267,7 +274,9 @@ fn execute (input_path : String)  -> Option<(i64, i64)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 21!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -1,132 +1,617 @@
 // Exercise 3: get the common element between two halves of a string.
 
 // For reading/parsing
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, prelude::*, BufReader};
 
-// For hashSet
-use std::collections::HashSet;
+// Utility
+use common::cli::DayArgs;
+use common::memory;
 
-// Converting the character in a priority. 
-// According to the AoC:
+// The priority of each item type, by its character.
+// According to the AoC, the default:
 // - Lowercase item types a through z have priorities 1 through 26.
 // - Uppercase item types A through Z have priorities 27 through 52.
-fn get_character_score(input: &u8) -> u8 {
-    // Assuming the character is either upper case or lower case letter,
-    // I just need to shift from the ascii value.
-    match input {
-        65..=90 => return input - 65 + 26 + 1,
-        97..=122 => return input - 97 + 1,
-        _ => panic!("wrong input character!"),
-    }
+#[derive(Clone, Debug)]
+struct PriorityTable {
+    priorities : [Option<u8>; 256],
 }
 
+impl PriorityTable {
+    fn letters() -> PriorityTable {
+        PriorityTable::parse("a-z 1\nA-Z 27\n").unwrap()
+    }
 
-// Searching the common element between two slices of u8
-fn search_common_between_char_slices(input1 : &[u8], input2 : &[u8]) -> u8 {
-    for left_element in input1 {
-        for right_element in input2 {
-            if left_element == right_element {
-                return right_element.to_owned();
+    // A table definition, one "<item> <priority>" or "<first>-<last> <first priority>" per line,
+    // the items of a range getting the following priorities. The "//" lines are comments,
+    // since '#' can be an item.
+    //   a-z 1
+    //   A-Z 27
+    //   0-9 53
+    //   ! 63
+    fn parse(text : &str) -> Result<PriorityTable, String> {
+        let mut table = PriorityTable { priorities : [None; 256] };
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with("//")) {
+            let (items, priority) = line.rsplit_once(' ').ok_or(format!("expected '<items> <priority>', got '{}'", line))?;
+            let priority = priority.parse::<u8>().map_err(|_| format!("'{}' is not a priority from 0 to 255", priority))?;
+            let (first, last) = match *items.trim().as_bytes() {
+                [item] => (item, item),
+                [first, b'-', last] if first <= last => (first, last),
+                _ => return Err(format!("'{}' is neither an item nor a range of items", items.trim())),
+            };
+            for (offset, item) in (first..=last).enumerate() {
+                let item_priority = u8::try_from(priority as usize + offset).map_err(|_| format!("the priorities of '{}' go over 255", items.trim()))?;
+                if table.priorities[item as usize].replace(item_priority).is_some() {
+                    return Err(format!("the item '{}' has two priorities", item as char));
+                }
             }
         }
+        Ok(table)
+    }
+
+    fn priority(&self, item : u8) -> Result<u8, String> {
+        self.priorities[item as usize].ok_or(format!("the item '{}' has no priority", item.escape_ascii()))
     }
-    0
 }
 
 
-// Searching the common element between two slices of u8
-fn search_common_elements_between_char_slices(input1 : &[u8], input2 : &[u8]) -> Option<Vec::<u8>> {
-    let mut common_elements = Vec::<u8>::new();
-    for left_element in input1 {
-        for right_element in input2 {
-            if left_element == right_element {
-                common_elements.push(right_element.to_owned());
-                break;
+// A set of item types, one bit per priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct ItemSet([u64; 4]);
+
+impl ItemSet {
+    fn new() -> ItemSet {
+        ItemSet([0; 4])
+    }
+
+    fn single(priority : u8) -> ItemSet {
+        let mut words = [0; 4];
+        words[priority as usize / 64] = 1 << (priority % 64);
+        ItemSet(words)
+    }
+
+    fn from_items(items : &[u8], table : &PriorityTable) -> Result<ItemSet, String> {
+        items.iter().try_fold(ItemSet::new(), |set, &item| Ok(set.union(&ItemSet::single(table.priority(item)?))))
+    }
+
+    fn contains(&self, priority : u8) -> bool {
+        self.0[priority as usize / 64] & (1 << (priority % 64)) != 0
+    }
+
+    fn intersection(&self, other : &ItemSet) -> ItemSet {
+        ItemSet(std::array::from_fn(|word| self.0[word] & other.0[word]))
+    }
+
+    fn union(&self, other : &ItemSet) -> ItemSet {
+        ItemSet(std::array::from_fn(|word| self.0[word] | other.0[word]))
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    // The priorities in the set, in increasing order.
+    fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=255).filter(|&priority| self.contains(priority))
+    }
+}
+
+
+// The single item type shared by all the lists, as a priority.
+fn find_single_common(lists : &[&[u8]], table : &PriorityTable) -> Result<u8, String> {
+    let common = lists.iter()
+        .map(|items| ItemSet::from_items(items, table))
+        .collect::<Result<Vec<ItemSet>, String>>()?
+        .into_iter()
+        .reduce(|common, items| common.intersection(&items))
+        .unwrap_or_default();
+    if common.is_empty() {
+        return Err("no item type in common".to_string());
+    }
+    match common.len() {
+        1 => Ok(common.iter().next().unwrap()),
+        count => Err(format!("{} item types in common instead of one", count)),
+    }
+}
+
+
+// The priorities of the badges of each group of rucksacks: the item type carried by all of them.
+fn find_badges(inventory_vec : &[String], group_size : usize, table : &PriorityTable) -> Result<Vec<u8>, String> {
+    if group_size == 0 {
+        return Err("the groups cannot be empty".to_string());
+    }
+    if !inventory_vec.len().is_multiple_of(group_size) {
+        return Err(format!("{} rucksacks cannot be split in groups of {}", inventory_vec.len(), group_size));
+    }
+    inventory_vec.chunks(group_size).enumerate()
+        .map(|(group_index, group)| {
+            let lists : Vec<&[u8]> = group.iter().map(|line| line.as_bytes()).collect();
+            find_single_common(&lists, table).map_err(|message| format!("group {}: {}", group_index + 1, message))
+        })
+        .collect()
+}
+
+
+
+// A group of rucksacks and the only item type they all carry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BadgeGroup {
+    rucksacks : Vec<usize>,
+    badge : u8,
+}
+
+// The backtracking search of a partition in badge groups. The free rucksack with the
+// fewest possible groups is placed first, and the search backs off as soon as one of
+// them has none left. A group picks one item type of the rucksack as the badge, then the
+// other members among the free rucksacks carrying it, keeping the running intersection,
+// until the group is full and shares only the badge.
+struct GroupSearch {
+    item_sets : Vec<ItemSet>,
+    holders : Vec<Vec<usize>>, // The rucksacks carrying each priority.
+    group_size : usize,
+    assigned : Vec<bool>,
+    groups : Vec<BadgeGroup>,
+}
+
+impl GroupSearch {
+    fn complete(&mut self) -> bool {
+        let mut most_constrained : Option<(usize, usize)> = None;
+        for rucksack in (0..self.assigned.len()).filter(|&rucksack| !self.assigned[rucksack]) {
+            let limit = most_constrained.map_or(usize::MAX, |(_, count)| count);
+            match self.count_groups(rucksack, limit) {
+                0 => return false,
+                count if count < limit => most_constrained = Some((rucksack, count)),
+                _ => (),
+            }
+        }
+        let Some((first, _)) = most_constrained else {
+            return true;
+        };
+
+        self.assigned[first] = true;
+        let first_set = self.item_sets[first];
+        for badge in first_set.iter() {
+            if self.fill(badge, first_set, &mut vec![first], 0) {
+                return true;
+            }
+        }
+        self.assigned[first] = false;
+        false
+    }
+
+    fn fill(&mut self, badge : u8, common : ItemSet, members : &mut Vec<usize>, next_holder : usize) -> bool {
+        if members.len() == self.group_size {
+            if common.len() != 1 {
+                return false;
+            }
+            self.groups.push(BadgeGroup { rucksacks : members.clone(), badge });
+            if self.complete() {
+                return true;
+            }
+            self.groups.pop();
+            return false;
+        }
+        for holder_index in next_holder..self.holders[badge as usize].len() {
+            let rucksack = self.holders[badge as usize][holder_index];
+            if self.assigned[rucksack] {
+                continue;
             }
+            self.assigned[rucksack] = true;
+            members.push(rucksack);
+            if self.fill(badge, common.intersection(&self.item_sets[rucksack]), members, holder_index + 1) {
+                return true;
+            }
+            members.pop();
+            self.assigned[rucksack] = false;
         }
+        false
     }
 
-    // Removing duplicates, if any:
-    let common_elements = common_elements
-    .into_iter()
-    .collect::<HashSet<_>>()
-    .into_iter()
-    .collect::<Vec<_>>();
+    // The groups the free rucksack could still make, counted up to the limit.
+    fn count_groups(&self, rucksack : usize, limit : usize) -> usize {
+        let mut count = 0;
+        for badge in self.item_sets[rucksack].iter() {
+            count += self.count_members(rucksack, badge, self.item_sets[rucksack], 1, 0, limit - count);
+            if count >= limit {
+                break;
+            }
+        }
+        count
+    }
 
-    Some(common_elements)
+    fn count_members(&self, rucksack : usize, badge : u8, common : ItemSet, members : usize, next_holder : usize, limit : usize) -> usize {
+        if members == self.group_size {
+            return usize::from(common.len() == 1);
+        }
+        let mut count = 0;
+        for holder_index in next_holder..self.holders[badge as usize].len() {
+            let other = self.holders[badge as usize][holder_index];
+            if other == rucksack || self.assigned[other] {
+                continue;
+            }
+            count += self.count_members(rucksack, badge, common.intersection(&self.item_sets[other]), members + 1, holder_index + 1, limit - count);
+            if count >= limit {
+                break;
+            }
+        }
+        count
+    }
 }
 
 
+// Groups of the given size covering all the rucksacks, in any order, each sharing exactly
+// one item type. None when there is no such partition. The random groups often share a
+// single item type too, so when several partitions exist the first one found is returned.
+fn discover_groups(inventory_vec : &[String], group_size : usize, table : &PriorityTable) -> Result<Option<Vec<BadgeGroup>>, String> {
+    if group_size == 0 {
+        return Err("the groups cannot be empty".to_string());
+    }
+    if !inventory_vec.len().is_multiple_of(group_size) {
+        return Err(format!("{} rucksacks cannot be split in groups of {}", inventory_vec.len(), group_size));
+    }
+    let item_sets = inventory_vec.iter()
+        .map(|line| ItemSet::from_items(line.as_bytes(), table))
+        .collect::<Result<Vec<ItemSet>, String>>()?;
+    let mut holders = vec![Vec::<usize>::new(); 256];
+    for (rucksack, item_set) in item_sets.iter().enumerate() {
+        for priority in item_set.iter() {
+            holders[priority as usize].push(rucksack);
+        }
+    }
+    let mut search = GroupSearch { assigned : vec![false; item_sets.len()], item_sets, holders, group_size, groups : Vec::new() };
+    Ok(search.complete().then_some(search.groups))
+}
 
-// Main Function
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 3!");
-
-    // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
-    let reader = BufReader::new(file);
 
-    // Reading in two vectors, then using the "zip" functionality to work along them
+// The rucksacks, whole and split in their two compartments.
+fn read_inventory(reader : impl BufRead) -> (Vec<String>, Vec<(String, String)>) {
     let mut inventory_vec = Vec::<String>::new();
     let mut inventory_split_vec = Vec::<(String, String)>::new();
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
+    for line in reader.lines().map_while(Result::ok) {
+
+        // Reading the line length:
+        let size = line.len();
+        if size % 2 != 0 {
+            panic!("wrong number of arguments")
+        }
+        let size = size/2;
+
+        inventory_split_vec.push((line[..size].to_owned(), line[size..].to_owned()));
+        inventory_vec.push(line.to_owned());
+    }
+    (inventory_vec, inventory_split_vec)
+}
+
 
-            // Reading the line length:
-            let size = line.len();
-            if size % 2 != 0 {
-                panic!("wrong number of arguments")
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Compartment {
+    Left,
+    Right,
+}
+
+// One item taken out of a compartment and put in the other one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Move {
+    item : u8,
+    from : Compartment,
+}
+
+// One item of the rucksack swapped with one of another rucksack of the group,
+// each taking the place of the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Exchange {
+    item : u8,
+    other_rucksack : usize,
+    other_item : u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RepackPlan {
+    exchange : Option<Exchange>,
+    moves : Vec<Move>,
+    repacked : Rucksack,
+}
+
+// The items of the left and right compartments.
+type Rucksack = (Vec<u8>, Vec<u8>);
+
+
+// The fewest moves putting every item type in a single compartment, keeping the
+// two compartments of the same size. Each type goes entirely to one side, so the
+// choice is a subset of the types filling exactly the left compartment: a knapsack
+// where a type costs the items it has on the other side.
+// The moves alternate between the two directions, so the sizes never differ by more than one.
+fn plan_compartments(left : &[u8], right : &[u8]) -> Option<Vec<Move>> {
+    let mut counts = [[0usize; 2]; 256];
+    for &item in left {
+        counts[item as usize][0] += 1;
+    }
+    for &item in right {
+        counts[item as usize][1] += 1;
+    }
+    let types : Vec<usize> = (0..256).filter(|&item| counts[item] != [0, 0]).collect();
+    let size = left.len();
+
+    // costs[index][filled]: the fewest moves for the first types, filling that much of the left.
+    let mut costs = vec![vec![None; size + 1]; types.len() + 1];
+    costs[0][0] = Some(0);
+    for (index, &item) in types.iter().enumerate() {
+        let [on_left, on_right] = counts[item];
+        for filled in 0..=size {
+            let Some(cost) = costs[index][filled] else {
+                continue;
+            };
+            let mut keep = |filled : usize, cost : usize| {
+                if costs[index + 1][filled].is_none_or(|best| cost < best) {
+                    costs[index + 1][filled] = Some(cost);
+                }
+            };
+            keep(filled, cost + on_left);
+            if filled + on_left + on_right <= size {
+                keep(filled + on_left + on_right, cost + on_right);
             }
-            let size = size/2;
+        }
+    }
+    costs[types.len()][size]?;
 
-            inventory_split_vec.push((line[..size].to_owned(), line[size..].to_owned()));
-            inventory_vec.push(line.to_owned());
+    // Going back through the types to find which ones fill the left.
+    let mut to_left = Vec::<Move>::new();
+    let mut to_right = Vec::<Move>::new();
+    let mut filled = size;
+    for (index, &item) in types.iter().enumerate().rev() {
+        let [on_left, on_right] = counts[item];
+        let cost = costs[index + 1][filled].unwrap();
+        if filled >= on_left + on_right && costs[index][filled - on_left - on_right].map(|previous| previous + on_right) == Some(cost) {
+            filled -= on_left + on_right;
+            to_left.extend((0..on_right).map(|_| Move { item : item as u8, from : Compartment::Right }));
+        }
+        else {
+            to_right.extend((0..on_left).map(|_| Move { item : item as u8, from : Compartment::Left }));
         }
     }
+    Some(to_right.into_iter().zip(to_left).flat_map(|(first, second)| [first, second]).collect())
+}
+
+
+// The two compartments after the moves.
+fn apply_moves(left : &[u8], right : &[u8], moves : &[Move]) -> Rucksack {
+    let mut compartments = [left.to_vec(), right.to_vec()];
+    for item_move in moves {
+        let (from, to) = match item_move.from {
+            Compartment::Left => (0, 1),
+            Compartment::Right => (1, 0),
+        };
+        let position = compartments[from].iter().position(|&item| item == item_move.item).unwrap();
+        compartments[from].remove(position);
+        compartments[to].push(item_move.item);
+    }
+    let [left, right] = compartments;
+    (left, right)
+}
+
+
+// A repacking plan for every rucksack. With groups, a rucksack that cannot be repacked
+// alone can exchange one item with another rucksack of its group: the exchange costing
+// the fewest moves, counting the exchange as two, is chosen, then the other rucksack is
+// planned with what it received.
+fn plan_repacking(inventory_split_vec : &[(String, String)], group_size : Option<usize>) -> Vec<Result<RepackPlan, String>> {
+    let mut contents : Vec<Rucksack> = inventory_split_vec.iter()
+        .map(|(left, right)| (left.as_bytes().to_vec(), right.as_bytes().to_vec()))
+        .collect();
+    let mut exchanges = vec![None; contents.len()];
+
+    if let Some(group_size) = group_size.filter(|&group_size| group_size > 1) {
+        for rucksack in 0..contents.len() {
+            if plan_compartments(&contents[rucksack].0, &contents[rucksack].1).is_some() {
+                continue;
+            }
+            let group_start = rucksack - rucksack % group_size;
+            let mut best : Option<(usize, Exchange, Rucksack, Rucksack)> = None;
+            for other_rucksack in (group_start..(group_start + group_size).min(contents.len())).filter(|&other| other != rucksack) {
+                for (position, other_position) in exchange_positions(&contents[rucksack], &contents[other_rucksack]) {
+                    let mut mine = contents[rucksack].clone();
+                    let mut other = contents[other_rucksack].clone();
+                    let item = swap_item(&mut mine, position, &mut other, other_position);
+                    let (Some(my_moves), Some(other_moves)) = (plan_compartments(&mine.0, &mine.1), plan_compartments(&other.0, &other.1)) else {
+                        continue;
+                    };
+                    let cost = 2 + my_moves.len() + other_moves.len();
+                    if best.as_ref().is_none_or(|(best_cost, ..)| cost < *best_cost) {
+                        let exchange = Exchange { item, other_rucksack, other_item : get_item(&mine, position) };
+                        best = Some((cost, exchange, mine, other));
+                    }
+                }
+            }
+            if let Some((_, exchange, mine, other)) = best {
+                contents[rucksack] = mine;
+                contents[exchange.other_rucksack] = other;
+                exchanges[rucksack] = Some(exchange);
+            }
+        }
+    }
+
+    contents.iter().zip(exchanges)
+        .map(|((left, right), exchange)| match plan_compartments(left, right) {
+            Some(moves) => Ok(RepackPlan { exchange, repacked : apply_moves(left, right, &moves), moves }),
+            None => Err("no split of the item types fills the two compartments equally".to_string()),
+        })
+        .collect()
+}
+
+
+// The positions of the items worth exchanging between two rucksacks: the first
+// position of each item type of one, with the first of each different type of the other.
+fn exchange_positions(mine : &Rucksack, other : &Rucksack) -> Vec<(usize, usize)> {
+    let first_positions = |rucksack : &Rucksack| -> Vec<(usize, u8)> {
+        let mut seen = [false; 256];
+        rucksack.0.iter().chain(&rucksack.1).enumerate()
+            .filter(|&(_, &item)| !std::mem::replace(&mut seen[item as usize], true))
+            .map(|(position, &item)| (position, item))
+            .collect()
+    };
+    let other_positions = first_positions(other);
+    first_positions(mine).into_iter()
+        .flat_map(|(position, item)| other_positions.iter()
+            .filter(move |&&(_, other_item)| other_item != item)
+            .map(move |&(other_position, _)| (position, other_position)))
+        .collect()
+}
+
+// The item at a position counted across the left then the right compartment.
+fn get_item(rucksack : &Rucksack, position : usize) -> u8 {
+    *rucksack.0.iter().chain(&rucksack.1).nth(position).unwrap()
+}
+
+fn item_slot(rucksack : &mut Rucksack, position : usize) -> &mut u8 {
+    let left_size = rucksack.0.len();
+    if position < left_size { &mut rucksack.0[position] } else { &mut rucksack.1[position - left_size] }
+}
+
+// Swaps two items and returns the one that left the first rucksack.
+fn swap_item(mine : &mut Rucksack, position : usize, other : &mut Rucksack, other_position : usize) -> u8 {
+    let item = *item_slot(mine, position);
+    *item_slot(mine, position) = *item_slot(other, other_position);
+    *item_slot(other, other_position) = item;
+    item
+}
+
+
+
+// The move list of each rucksack, one line each.
+fn describe_plans(plans : &[Result<RepackPlan, String>]) -> String {
+    let mut description = String::new();
+    let mut total_moves = 0;
+    for (rucksack, plan) in plans.iter().enumerate() {
+        let plan = match plan {
+            Ok(plan) => plan,
+            Err(message) => {
+                description += &format!("Rucksack {} cannot be repacked: {}.\n", rucksack + 1, message);
+                continue;
+            },
+        };
+        let mut steps = Vec::<String>::new();
+        if let Some(exchange) = plan.exchange {
+            steps.push(format!("exchange '{}' for '{}' of rucksack {}", exchange.item as char, exchange.other_item as char, exchange.other_rucksack + 1));
+            total_moves += 2;
+        }
+        for item_move in &plan.moves {
+            let side = match item_move.from {
+                Compartment::Left => "right",
+                Compartment::Right => "left",
+            };
+            steps.push(format!("'{}' to the {}", item_move.item as char, side));
+        }
+        total_moves += plan.moves.len();
+        if steps.is_empty() {
+            description += &format!("Rucksack {}: nothing to move.\n", rucksack + 1);
+        }
+        else {
+            let (left, right) = &plan.repacked;
+            description += &format!("Rucksack {}: {}, giving {}|{}.\n", rucksack + 1, steps.join(", "), left.escape_ascii(), right.escape_ascii());
+        }
+    }
+    description += &format!("{} moves in total.\n", total_moves);
+    description
+}
+
+
+// Primary Function
+fn execute (input_path : String, group_size : usize, table : &PriorityTable)  -> Option<(i32, i32)> {
+
+    // Handling the reading/parsing
+    let file = File::open(input_path).unwrap();
+    let reader = BufReader::new(file);
+
+    // Reading in two vectors, then using the "zip" functionality to work along them
+    let (inventory_vec, inventory_split_vec) = read_inventory(reader);
     
     // for each, searching the common element
     let mut total_sum : i32 = 0;
-    for element in &inventory_split_vec {
-        // Searching on both lines - complexity is n^2 but the characters are only 27 tops.
-        total_sum += get_character_score(
-            &search_common_between_char_slices(element.0.as_bytes(), element.1.as_bytes())) as i32;
+    for (line_index, element) in inventory_split_vec.iter().enumerate() {
+        match find_single_common(&[element.0.as_bytes(), element.1.as_bytes()], table) {
+            Ok(priority) => total_sum += priority as i32,
+            Err(message) => {
+                println!("Rucksack {}: {}.", line_index + 1, message);
+                return None;
+            },
+        }
     }
 
     // And returning the output.
-    println!("Result of Part 1 is {}", total_sum);
+    let result_part_1 = total_sum;
+    memory::report_part(1);
 
-    // For Part 2 I must find the common item between any truple of lines.
-    // I am now implementing a brutal approach since (once again) the maximum number of searches goes with 27^3
-    if inventory_vec.len() % 3 != 0 {
-        panic!("Inventory size not multiple of 3!");
-    }
-    let mut total_sum : i32 = 0;
-    for inventory_index in (0..inventory_vec.len()).step_by(3) {
-        // Comparing 1 and 2:
-        let common_1_2 = 
-        search_common_elements_between_char_slices(
-            inventory_vec[inventory_index].as_bytes(), 
-            inventory_vec[inventory_index + 1].as_bytes()).unwrap();
-        let common_1_2 = common_1_2.as_slice();
-        //println!("1_2: Found {} common elements: {}", common_1_2.len(), String::from_utf8(common_1_2.to_vec()).unwrap());
+    // For Part 2 I must find the common item of each group of lines.
+    let total_sum : i32 = match find_badges(&inventory_vec, group_size, table) {
+        Ok(badges) => badges.iter().map(|&priority| priority as i32).sum(),
+        Err(message) => {
+            println!("Cannot find the badges: {}.", message);
+            return None;
+        },
+    };
 
-        let common_1_2_3 =
-        search_common_elements_between_char_slices(
-            common_1_2,
-            inventory_vec[inventory_index + 2].as_bytes()).unwrap();
-        let common_1_2_3 = common_1_2_3.as_slice();
-        //println!("X_3: Found {} common elements: {}", common_1_2_3.len(), String::from_utf8(common_1_2_3.to_vec()).unwrap());
+    // Returning the common element sum:
+    let result_part_2 = total_sum;
+
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
 
-        if common_1_2_3.len() != 1 {
-            panic!("Not a single element has been found between the three!");
+
+// Main 
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 3!");
+
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // The badges are searched in groups of 3 rucksacks, or of K with --group <K>.
+    // Other item types get their priorities from --priorities <file>, see PriorityTable::parse.
+    // With --repack the moves separating the item types of each rucksack are listed, with
+    // --repack-groups the rucksacks may also exchange an item inside their badge group.
+    // With --discover the groups are searched among all the rucksacks, in any order.
+    let mut args = DayArgs::from_env();
+    let group_size = match args.take_option("--group", 1) {
+        Some(values) => values[0].parse::<usize>().expect("--group expects a number of rucksacks"),
+        None => 3,
+    };
+    let table = match args.take_option("--priorities", 1) {
+        Some(values) => PriorityTable::parse(&fs::read_to_string(&values[0])?)
+            .unwrap_or_else(|message| panic!("Wrong priority table in {}: {}", values[0], message)),
+        None => PriorityTable::letters(),
+    };
+    let repack_mode = args.take_switch("--repack");
+    let repack_groups_mode = args.take_switch("--repack-groups");
+    let discover_mode = args.take_switch("--discover");
+    let input_path = args.input_path("./data/input.txt");
+    if discover_mode {
+        let (inventory_vec, _) = read_inventory(BufReader::new(File::open(&input_path)?));
+        match discover_groups(&inventory_vec, group_size, &table) {
+            Ok(Some(groups)) => {
+                for (group_index, group) in groups.iter().enumerate() {
+                    let rucksacks : Vec<String> = group.rucksacks.iter().map(|rucksack| (rucksack + 1).to_string()).collect();
+                    println!("Group {}: rucksacks {} share the badge of priority {}.", group_index + 1, rucksacks.join(", "), group.badge);
+                }
+                println!("The badges sum to {}.", groups.iter().map(|group| group.badge as i32).sum::<i32>());
+            },
+            Ok(None) => println!("The rucksacks cannot be split in groups of {} sharing one item type.", group_size),
+            Err(message) => println!("Cannot search the groups: {}.", message),
         }
-        
-        total_sum += get_character_score(&common_1_2_3[0]) as i32;
     }
-
-    // Returning the common element sum:
-    println!("For Part 2 the sum of priorities is {}.", total_sum);
+    if repack_mode || repack_groups_mode {
+        let (_, inventory_split_vec) = read_inventory(BufReader::new(File::open(&input_path)?));
+        let plans = plan_repacking(&inventory_split_vec, repack_groups_mode.then_some(group_size));
+        print!("{}", describe_plans(&plans));
+    }
+    let Some(results) = execute(input_path, group_size, &table) else {
+        println!("No results.");
+        return Ok(());
+    };
+    
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
@@ -139,14 +624,138 @@ mod tests {
 
     #[test]
     fn test_character_scores() {
-        assert_eq!(get_character_score(&('a' as u8)), 1);
-        assert_eq!(get_character_score(&('z' as u8)), 26);
-        assert_eq!(get_character_score(&('A' as u8)), 27);
-        assert_eq!(get_character_score(&('Z' as u8)), 52);
+        let table = PriorityTable::letters();
+        assert_eq!(table.priority(b'a'), Ok(1));
+        assert_eq!(table.priority(b'z'), Ok(26));
+        assert_eq!(table.priority(b'A'), Ok(27));
+        assert_eq!(table.priority(b'Z'), Ok(52));
+        assert!(table.priority(b'0').is_err());
+    }
+
+    #[test]
+    fn test_priority_tables() {
+        let table = PriorityTable::parse(&fs::read_to_string("./data/priorities.txt").unwrap()).unwrap();
+        assert_eq!(table.priority(b'Z'), Ok(52));
+        assert_eq!(table.priority(b'0'), Ok(53));
+        assert_eq!(table.priority(b'-'), Ok(66));
+        assert_eq!(table.priority(b'~'), Ok(200));
+        assert!(table.priority(b' ').is_err());
+
+        assert!(PriorityTable::parse("a-z 1\nx 30").is_err());
+        assert!(PriorityTable::parse("a-z 250").is_err());
+        assert!(PriorityTable::parse("z-a 1").is_err());
+        assert!(PriorityTable::parse("abc 1").is_err());
+        assert!(PriorityTable::parse("a 256").is_err());
+
+        // The extended items are found like the letters.
+        assert_eq!(execute("./data/test_extended.txt".to_string(), 3, &table), Some((53 + 66 + 200, 200)));
+        assert_eq!(execute("./data/test_extended.txt".to_string(), 3, &PriorityTable::letters()), None);
     }
 
     #[test]
     fn test_find_common_characters() {
-        assert_eq!('a' as u8, search_common_between_char_slices("abcd".as_bytes(), "TKfaLK".as_bytes()));
+        let table = PriorityTable::letters();
+        assert_eq!(find_single_common(&["abcd".as_bytes(), "TKfaLK".as_bytes()], &table), Ok(1));
+        assert!(find_single_common(&["abcd".as_bytes(), "TKfLK".as_bytes()], &table).is_err());
+        assert!(find_single_common(&["abcd".as_bytes(), "dcK".as_bytes()], &table).is_err());
+    }
+
+    #[test]
+    fn test_item_set() {
+        let table = PriorityTable::letters();
+        let left = ItemSet::from_items("abcZ".as_bytes(), &table).unwrap();
+        let right = ItemSet::from_items("cZzz".as_bytes(), &table).unwrap();
+        assert_eq!(left.len(), 4);
+        assert!(left.contains(52) && !left.contains(26));
+        assert_eq!(left.intersection(&right).iter().collect::<Vec<_>>(), vec![3, 52]);
+        assert_eq!(left.union(&right).iter().collect::<Vec<_>>(), vec![1, 2, 3, 26, 52]);
+        assert!(ItemSet::from_items("ab".as_bytes(), &table).unwrap().intersection(&right).is_empty());
+
+        // The priorities go up to 255.
+        let high = ItemSet::single(255).union(&ItemSet::single(64)).union(&ItemSet::single(0));
+        assert_eq!(high.iter().collect::<Vec<_>>(), vec![0, 64, 255]);
+        assert_eq!(high.intersection(&ItemSet::single(64)).len(), 1);
+    }
+
+    #[test]
+    fn test_badges() {
+        let table = PriorityTable::letters();
+        assert_eq!(execute("./data/test.txt".to_string(), 3, &table), Some((157, 70)));
+
+        let inventory_vec : Vec<String> = ["abX", "cdX", "Xef", "Xgh"].iter().map(|line| line.to_string()).collect();
+        assert_eq!(find_badges(&inventory_vec, 2, &table), Ok(vec![50, 50]));
+        assert_eq!(find_badges(&inventory_vec, 4, &table), Ok(vec![50]));
+        assert!(find_badges(&inventory_vec, 3, &table).is_err());
+        assert!(find_badges(&inventory_vec, 0, &table).is_err());
+        assert!(find_badges(&inventory_vec[..2], 1, &table).is_err());
+
+        // The 6 rucksacks of the example cannot make groups of 5.
+        assert_eq!(execute("./data/test.txt".to_string(), 5, &table), None);
+    }
+
+    #[test]
+    fn test_plan_compartments() {
+        // Each type must end on a single side, with 2 items per side.
+        let moves = plan_compartments(b"ab", b"cb").unwrap();
+        assert_eq!(moves.len(), 2);
+        let (left, right) = apply_moves(b"ab", b"cb", &moves);
+        assert_eq!((left.len(), right.len()), (2, 2));
+        assert!(left.iter().all(|item| !right.contains(item)));
+
+        assert_eq!(plan_compartments(b"ab", b"cd"), Some(vec![]));
+        assert_eq!(plan_compartments(b"aab", b"bbc"), Some(vec![
+            Move { item : b'b', from : Compartment::Left },
+            Move { item : b'c', from : Compartment::Right },
+        ]));
+        assert_eq!(plan_compartments(b"aa", b"ab"), None);
+
+        // The example rucksacks share one type each, and can all be split.
+        let (_, inventory_split_vec) = read_inventory(BufReader::new(File::open("./data/test.txt").unwrap()));
+        for (left, right) in &inventory_split_vec {
+            let moves = plan_compartments(left.as_bytes(), right.as_bytes()).unwrap();
+            let (left, right) = apply_moves(left.as_bytes(), right.as_bytes(), &moves);
+            assert_eq!(left.len(), right.len());
+            assert!(left.iter().all(|item| !right.contains(item)));
+        }
+    }
+
+    #[test]
+    fn test_plan_repacking() {
+        let inventory_split_vec = vec![("aa".to_string(), "ab".to_string()), ("cd".to_string(), "ce".to_string())];
+        let plans = plan_repacking(&inventory_split_vec, None);
+        assert!(plans[0].is_err());
+        assert_eq!(plans[1].as_ref().unwrap().moves.len(), 2);
+
+        // The first rucksack cannot split three 'a' alone, it gives one to the second.
+        let plans = plan_repacking(&inventory_split_vec, Some(2));
+        let plan = plans[0].as_ref().unwrap();
+        let exchange = plan.exchange.unwrap();
+        assert_eq!((exchange.item, exchange.other_rucksack), (b'a', 1));
+        assert!(plans[1].as_ref().unwrap().exchange.is_none());
+        assert!(describe_plans(&plans).starts_with("Rucksack 1: exchange 'a' for "));
+        assert!(describe_plans(&plan_repacking(&inventory_split_vec, None)).starts_with("Rucksack 1 cannot be repacked"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_discover_groups() {
+        let table = PriorityTable::letters();
+        let (inventory_vec, _) = read_inventory(BufReader::new(File::open("./data/test_shuffled.txt").unwrap()));
+        let groups = discover_groups(&inventory_vec, 3, &table).unwrap().unwrap();
+        assert_eq!(groups.len(), 2);
+        let mut covered : Vec<usize> = groups.iter().flat_map(|group| group.rucksacks.clone()).collect();
+        covered.sort();
+        assert_eq!(covered, (0..6).collect::<Vec<_>>());
+        for group in &groups {
+            let lists : Vec<&[u8]> = group.rucksacks.iter().map(|&rucksack| inventory_vec[rucksack].as_bytes()).collect();
+            assert_eq!(find_single_common(&lists, &table), Ok(group.badge));
+        }
+
+        // The twins share two letters and the others none.
+        let inventory_vec : Vec<String> = ["ab", "cd", "ab", "cd"].iter().map(|line| line.to_string()).collect();
+        assert_eq!(discover_groups(&inventory_vec, 2, &table), Ok(None));
+        let inventory_vec : Vec<String> = ["ab", "bc", "cd", "da"].iter().map(|line| line.to_string()).collect();
+        assert_eq!(discover_groups(&inventory_vec, 2, &table).unwrap().unwrap().len(), 2);
+        assert!(discover_groups(&inventory_vec, 3, &table).is_err());
+        assert!(discover_groups(&inventory_vec, 0, &table).is_err());
+    }
+}
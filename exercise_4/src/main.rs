@@ -1,47 +1,339 @@
 // Exercise 4: get the common element between two halves of a string.
 
 // For reading/parsing
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, prelude::*, BufReader};
 use regex::Regex;
 
+// Utility
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use common::cli::DayArgs;
+use common::memory;
+
+
+// Parsing the syntax: AA-BB,CC-DD,... with any number of ranges.
+fn parse_elf_ranges(input : &str) -> Option<Vec<(u32, u32)>> {
+    let line_regex = Regex::new(r"^\d+-\d+(,\d+-\d+)*$").unwrap();
+    if !line_regex.is_match(input.trim()) {
+        return None;
+    }
+    let range_regex = Regex::new(r"(?P<start>\d+)-(?P<end>\d+)").unwrap();
+    range_regex.captures_iter(input)
+        .map(|caps| {
+            let internal_parse = |key| caps.name(key).unwrap().as_str().parse::<u32>().ok();
+            Some((internal_parse("start")?, internal_parse("end")?))
+        })
+        .collect()
+}
+
 
 // Parsing the syntax: AA-BB,CC-DD
 fn parse_elf_assignments(input : &str) -> Option<((u32, u32), (u32, u32))> {
-    let regex_string = 
-        r"(?P<val1>\d+)-(?P<val2>\d+),(?P<val3>\d+)-(?P<val4>\d+)";
-    let regex = Regex::new(regex_string).unwrap();
-    match regex.captures(input) {
-        Some(caps) => {
-            let internal_parse = |key| {
-                caps.name(key).unwrap().as_str().parse::<u32>().unwrap()
-            };
-            let val1 = internal_parse("val1");
-            let val2 = internal_parse("val2");
-            let val3 = internal_parse("val3");
-            let val4 = internal_parse("val4");
-            Some(((val1, val2), (val3, val4)))
+    match parse_elf_ranges(input)?[..] {
+        [first, second] => Some((first, second)),
+        _ => None,
+    }
+}
+
+
+// The sections of the two elves of a line.
+type AssignmentPair = ((u32, u32), (u32, u32));
+
+fn read_assignments(reader : impl BufRead) -> Vec<AssignmentPair> {
+    let mut elf_pairs_assignments = Vec::<AssignmentPair>::new();
+    for line in reader.lines().map_while(Result::ok) {
+        elf_pairs_assignments.push(parse_elf_assignments(&line.to_owned()).unwrap());
+    }
+    elf_pairs_assignments
+}
+
+
+// All the ranges of each line, for the lines with any number of elves.
+fn read_assignment_lines(reader : impl BufRead) -> Vec<Vec<(u32, u32)>> {
+    reader.lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_elf_ranges(&line).unwrap_or_else(|| panic!("Wrong assignment line: {}", line)))
+        .collect()
+}
+
+
+// The sections of the whole file, between the first and the last assigned one, as
+// runs of sections with the same number of elves.
+#[derive(Debug, PartialEq)]
+struct Coverage {
+    max_elves : usize,
+    max_sections : Vec<(u32, u32)>,
+    uncovered : Vec<(u32, u32)>,
+    covered_more_than_once : Vec<(u32, u32)>,
+}
+
+impl Coverage {
+    // A sweep line over the starts and the ends of the ranges: the number of elves only
+    // changes at those sections, so the runs between two of them are counted at once.
+    fn sweep(assignments : &[Vec<(u32, u32)>]) -> Coverage {
+        let mut events = Vec::<(u64, i64)>::new();
+        for &(start, end) in assignments.iter().flatten() {
+            events.push((start as u64, 1));
+            events.push((end as u64 + 1, -1));
+        }
+        events.sort_unstable();
+
+        // The runs of sections with their number of elves.
+        let mut runs = Vec::<(u32, u32, usize)>::new();
+        let mut elves = 0i64;
+        for (index, &(position, change)) in events.iter().enumerate() {
+            elves += change;
+            if let Some(&(next_position, _)) = events.get(index + 1) {
+                if next_position > position {
+                    runs.push((position as u32, (next_position - 1) as u32, elves as usize));
+                }
+            }
+        }
+
+        let max_elves = runs.iter().map(|&(_, _, elves)| elves).max().unwrap_or(0);
+        let select = |keep : &dyn Fn(usize) -> bool| merge_runs(runs.iter().filter(|&&(_, _, elves)| keep(elves)).map(|&(start, end, _)| (start, end)));
+        Coverage {
+            max_elves,
+            max_sections : select(&|elves| elves == max_elves && elves > 0),
+            uncovered : select(&|elves| elves == 0),
+            covered_more_than_once : select(&|elves| elves > 1),
+        }
+    }
+
+    fn draw(&self) -> String {
+        let format_ranges = |ranges : &[(u32, u32)]| -> String {
+            if ranges.is_empty() {
+                return "none".to_string();
+            }
+            format_elf_ranges(ranges)
+        };
+        let sections = |ranges : &[(u32, u32)]| ranges.iter().map(|&(start, end)| (end - start + 1) as u64).sum::<u64>();
+        format!("At most {} elves on the sections {}.\n{} sections nobody covers: {}.\n{} sections covered more than once: {}.\n",
+            self.max_elves, format_ranges(&self.max_sections),
+            sections(&self.uncovered), format_ranges(&self.uncovered),
+            sections(&self.covered_more_than_once), format_ranges(&self.covered_more_than_once))
+    }
+}
+
+
+// Joins the ranges following each other, the ranges being sorted.
+fn merge_runs(ranges : impl Iterator<Item = (u32, u32)>) -> Vec<(u32, u32)> {
+    let mut merged = Vec::<(u32, u32)>::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.1 + 1 == start => last.1 = end,
+            _ => merged.push((start, end)),
         }
-        None => None,
     }
+    merged
 }
 
 
-// Main Function
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 4!");
+// Writing the syntax: AA-BB,CC-DD,...
+fn format_elf_ranges(ranges : &[(u32, u32)]) -> String {
+    ranges.iter().map(|&(start, end)| format!("{}-{}", start, end)).collect::<Vec<_>>().join(",")
+}
+
+
+// Over this many ranges on a line, the reassignment search would take too long.
+const REASSIGN_MAX_RANGES : usize = 12;
+
+// The search of the overlap-free reassignment: the shrunk ranges are placed from left to
+// right, each one starting after the previous one ends. The state is the set of ranges
+// placed and the first free section; a range only needs to end on its own end or just
+// before another range starts or after another one ends, so only those ends are tried.
+struct Reassignment<'a> {
+    ranges : &'a [(u32, u32)],
+    best_placements : HashMap<(usize, u64), Option<Placement>>,
+}
+
+// The next range to place and its new end, with the sections kept from there.
+#[derive(Clone, Copy)]
+struct Placement {
+    kept : u64,
+    index : usize,
+    end : u64,
+}
+
+impl Reassignment<'_> {
+    fn candidate_ends(&self, start : u64, end : u64) -> Vec<u64> {
+        let mut ends = vec![start, end];
+        for &(other_start, other_end) in self.ranges {
+            ends.push(other_start as u64 - 1);
+            ends.push(other_end as u64);
+        }
+        ends.retain(|&candidate| start <= candidate && candidate <= end);
+        ends.sort_unstable();
+        ends.dedup();
+        ends
+    }
+
+    // The most sections the ranges not placed yet can keep, starting from the free section.
+    fn kept(&mut self, placed : usize, free : u64) -> Option<u64> {
+        if placed == (1 << self.ranges.len()) - 1 {
+            return Some(0);
+        }
+        if let Some(best) = self.best_placements.get(&(placed, free)) {
+            return best.map(|placement| placement.kept);
+        }
+        let mut best : Option<Placement> = None;
+        for (index, &(start, end)) in self.ranges.iter().enumerate() {
+            let start = free.max(start as u64);
+            if placed & (1 << index) != 0 || start > end as u64 {
+                continue;
+            }
+            for new_end in self.candidate_ends(start, end as u64) {
+                let Some(rest) = self.kept(placed | (1 << index), new_end + 1) else {
+                    continue;
+                };
+                let kept = new_end - start + 1 + rest;
+                if best.is_none_or(|placement| kept > placement.kept) {
+                    best = Some(Placement { kept, index, end : new_end });
+                }
+            }
+        }
+        self.best_placements.insert((placed, free), best);
+        best.map(|placement| placement.kept)
+    }
+}
+
+
+// The ranges shrunk so that no section is shared, removing the fewest sections and
+// keeping every range non-empty, in the same order as given.
+fn reassign(ranges : &[(u32, u32)]) -> Result<Vec<(u32, u32)>, String> {
+    if ranges.len() > REASSIGN_MAX_RANGES {
+        return Err(format!("{} ranges, the search handles at most {}", ranges.len(), REASSIGN_MAX_RANGES));
+    }
+    if let Some(&(start, end)) = ranges.iter().find(|&&(start, end)| start > end || start == 0) {
+        return Err(format!("the range {}-{} is not a range of sections", start, end));
+    }
+    let mut search = Reassignment { ranges, best_placements : HashMap::new() };
+    if search.kept(0, 0).is_none() {
+        return Err("the ranges cannot all keep a section of their own".to_string());
+    }
+
+    // Following the choices of the search.
+    let mut new_ranges = ranges.to_vec();
+    let (mut placed, mut free) = (0, 0);
+    while let Some(&Some(placement)) = search.best_placements.get(&(placed, free)) {
+        new_ranges[placement.index] = (free.max(ranges[placement.index].0 as u64) as u32, placement.end as u32);
+        placed |= 1 << placement.index;
+        free = placement.end + 1;
+    }
+    Ok(new_ranges)
+}
+
+
+// The sections removed from the ranges.
+fn shrinkage(ranges : &[(u32, u32)], new_ranges : &[(u32, u32)]) -> u64 {
+    let length = |&(start, end) : &(u32, u32)| (end - start + 1) as u64;
+    ranges.iter().map(length).sum::<u64>() - new_ranges.iter().map(length).sum::<u64>()
+}
+
+
+// Allen's relations between two intervals, reading the sections AA-BB as the interval
+// from the start of AA to the end of BB: two assignments "meet" when one ends on the
+// section just before the other starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AllenRelation {
+    Before,
+    Meets,
+    Overlaps,
+    Starts,
+    During,
+    Finishes,
+    Equals,
+    FinishedBy,
+    Contains,
+    StartedBy,
+    OverlappedBy,
+    MetBy,
+    After,
+}
+
+impl AllenRelation {
+    const ALL : [AllenRelation; 13] = [
+        AllenRelation::Before, AllenRelation::Meets, AllenRelation::Overlaps, AllenRelation::Starts,
+        AllenRelation::During, AllenRelation::Finishes, AllenRelation::Equals, AllenRelation::FinishedBy,
+        AllenRelation::Contains, AllenRelation::StartedBy, AllenRelation::OverlappedBy, AllenRelation::MetBy,
+        AllenRelation::After,
+    ];
+
+    // How the first assignment relates to the second one.
+    fn classify(first : (u32, u32), second : (u32, u32)) -> AllenRelation {
+        // Half-open intervals, so that the adjacent assignments meet.
+        let (first_start, first_end) = (first.0, first.1 + 1);
+        let (second_start, second_end) = (second.0, second.1 + 1);
+        if first_end < second_start {
+            AllenRelation::Before
+        }
+        else if first_end == second_start {
+            AllenRelation::Meets
+        }
+        else if second_end < first_start {
+            AllenRelation::After
+        }
+        else if second_end == first_start {
+            AllenRelation::MetBy
+        }
+        else {
+            match (first_start.cmp(&second_start), first_end.cmp(&second_end)) {
+                (Ordering::Less, Ordering::Less) => AllenRelation::Overlaps,
+                (Ordering::Less, Ordering::Equal) => AllenRelation::FinishedBy,
+                (Ordering::Less, Ordering::Greater) => AllenRelation::Contains,
+                (Ordering::Equal, Ordering::Less) => AllenRelation::Starts,
+                (Ordering::Equal, Ordering::Equal) => AllenRelation::Equals,
+                (Ordering::Equal, Ordering::Greater) => AllenRelation::StartedBy,
+                (Ordering::Greater, Ordering::Less) => AllenRelation::During,
+                (Ordering::Greater, Ordering::Equal) => AllenRelation::Finishes,
+                (Ordering::Greater, Ordering::Greater) => AllenRelation::OverlappedBy,
+            }
+        }
+    }
+
+    // One of the two assignments holds all the sections of the other one.
+    fn is_containment(self) -> bool {
+        matches!(self,
+            AllenRelation::Starts | AllenRelation::During | AllenRelation::Finishes | AllenRelation::Equals |
+            AllenRelation::FinishedBy | AllenRelation::Contains | AllenRelation::StartedBy)
+    }
+
+    // The two assignments share at least a section.
+    fn is_overlap(self) -> bool {
+        !matches!(self, AllenRelation::Before | AllenRelation::Meets | AllenRelation::MetBy | AllenRelation::After)
+    }
+}
+
+
+// How many pairs are in each relation, in the order of AllenRelation::ALL.
+fn relation_histogram(elf_pairs_assignments : &[AssignmentPair]) -> [usize; 13] {
+    let mut histogram = [0; 13];
+    for elem in elf_pairs_assignments {
+        histogram[AllenRelation::classify(elem.0, elem.1) as usize] += 1;
+    }
+    histogram
+}
+
+
+fn draw_histogram(histogram : &[usize; 13]) -> String {
+    let largest = histogram.iter().copied().max().unwrap_or(0).max(1);
+    AllenRelation::ALL.iter().zip(histogram)
+        .map(|(relation, &count)| format!("{:>13} {:>5} {}\n", format!("{:?}", relation), count, "#".repeat(count * 50 / largest)))
+        .collect()
+}
+
+
+// Primary Function
+fn execute (input_path : String)  -> Option<(u32, u32)> {
 
     // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
+    let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
     // Reading in two vectors, then using the "zip" functionality to work along them
-    let mut elf_pairs_assignments = Vec::<((u32, u32), (u32, u32))>::new();
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-            elf_pairs_assignments.push(parse_elf_assignments(&line.to_owned()).unwrap());
-        }
-    }
+    let elf_pairs_assignments = read_assignments(reader);
 
     println!("There are {} elements in the assignments.", elf_pairs_assignments.len());
 
@@ -52,27 +344,75 @@ fn main() -> io::Result<()> {
         }
     }
 
-    // Part 1 is extremely simple if the interval A is contained in B or vice versa, it's a +1 on the counter:
-    let mut counter = 0;
-    for elem in &elf_pairs_assignments {
-        // If elf 1 contains elf 2 or elf 2 contains elf 1.
-        if (elem.0.0 <= elem.1.0 && elem.0.1 >= elem.1.1) ||  (elem.1.0 <= elem.0.0 && elem.1.1 >= elem.0.1) {
-            counter += 1;
-        }
-    }
+    // Part 1 is extremely simple if the interval A is contained in B or vice versa, it's a +1 on the counter.
+    // Both parts are queries over the relations of the pairs.
+    let histogram = relation_histogram(&elf_pairs_assignments);
+    let count_relations = |query : fn(AllenRelation) -> bool| -> u32 {
+        AllenRelation::ALL.iter().zip(&histogram)
+            .filter(|(relation, _)| query(**relation))
+            .map(|(_, &count)| count as u32)
+            .sum()
+    };
+    let counter = count_relations(AllenRelation::is_containment);
 
-    println!("number of entirely overlapping sets is {}.", counter);
+    let result_part_1 = counter;
+    memory::report_part(1);
 
     // Turns out Part 2 is just as simple: counting if there is any overlap at all.
-    let mut counter = 0;
-    for elem in &elf_pairs_assignments {
-        // checking if the two sets are entirely non-intersecting. Either A is left from B or B is left from A.
-        if (elem.0.0 <= elem.1.0 && elem.1.0 <= elem.0.1) || (elem.1.0 <= elem.0.0 && elem.0.0 <= elem.1.1) {
-            counter += 1;
+    let counter = count_relations(AllenRelation::is_overlap);
+
+    let result_part_2 = counter;
+
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
+
+
+// Main 
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 4!");
+
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // With --relations the pairs are counted by their interval relation. With --coverage
+    // the sections of the whole file are counted, the lines having any number of ranges.
+    // With --reassign <file> the ranges of each line are shrunk to remove the overlaps.
+    let mut args = DayArgs::from_env();
+    let relations_mode = args.take_switch("--relations");
+    let coverage_mode = args.take_switch("--coverage");
+    let reassign_option = args.take_option("--reassign", 1);
+    let input_path = args.input_path("./data/input.txt");
+    if let Some(values) = reassign_option {
+        let assignments = read_assignment_lines(BufReader::new(File::open(&input_path)?));
+        let mut new_lines = String::new();
+        let mut total_shrinkage = 0;
+        for (line_index, ranges) in assignments.iter().enumerate() {
+            match reassign(ranges) {
+                Ok(new_ranges) => {
+                    total_shrinkage += shrinkage(ranges, &new_ranges);
+                    new_lines += &format_elf_ranges(&new_ranges);
+                },
+                Err(message) => {
+                    println!("Line {} is kept as it is: {}.", line_index + 1, message);
+                    new_lines += &format_elf_ranges(ranges);
+                },
+            }
+            new_lines += "\n";
         }
+        fs::write(&values[0], new_lines)?;
+        println!("Saved the assignments without overlaps in {}, {} sections removed.", values[0], total_shrinkage);
     }
-
-    println!("number of partially overlapping sets is {}.", counter);
+    if coverage_mode {
+        let assignments = read_assignment_lines(BufReader::new(File::open(&input_path)?));
+        print!("{}", Coverage::sweep(&assignments).draw());
+    }
+    if relations_mode {
+        let elf_pairs_assignments = read_assignments(BufReader::new(File::open(&input_path)?));
+        print!("{}", draw_histogram(&relation_histogram(&elf_pairs_assignments)));
+    }
+    let results = execute(input_path).unwrap();
+    
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
@@ -87,4 +427,90 @@ mod tests {
     fn test_elf_assignment() {
         assert_eq!(parse_elf_assignments("42-44,2-333").unwrap(), ((42,44),(2,333)));
     }
+
+    #[test]
+    fn test_elf_ranges() {
+        assert_eq!(parse_elf_ranges("1-3,2-5,9-9"), Some(vec![(1, 3), (2, 5), (9, 9)]));
+        assert_eq!(parse_elf_ranges("4-6"), Some(vec![(4, 6)]));
+        assert_eq!(parse_elf_ranges("4-6,"), None);
+        assert_eq!(parse_elf_ranges("4-6;1-2"), None);
+        assert_eq!(parse_elf_assignments("1-3,2-5,9-9"), None);
+    }
+
+    #[test]
+    fn test_coverage() {
+        // 1 2 3 4 5 6 7 8 9 10 11 12 13
+        // 1 3 2 2 2 1 0 0 1  0  0  1  1
+        let assignments = read_assignment_lines(BufReader::new(File::open("./data/test_multi.txt").unwrap()));
+        assert_eq!(assignments.len(), 3);
+        let coverage = Coverage::sweep(&assignments);
+        assert_eq!(coverage, Coverage {
+            max_elves : 3,
+            max_sections : vec![(2, 2)],
+            uncovered : vec![(7, 8), (10, 11)],
+            covered_more_than_once : vec![(2, 5)],
+        });
+        assert_eq!(coverage.draw().lines().nth(1), Some("4 sections nobody covers: 7-8,10-11."));
+
+        let coverage = Coverage::sweep(&read_assignment_lines(BufReader::new(File::open("./data/test.txt").unwrap())));
+        assert_eq!((coverage.max_elves, coverage.max_sections.clone()), (8, vec![(6, 6)]));
+        assert!(coverage.uncovered.is_empty());
+        assert_eq!(Coverage::sweep(&[]).max_elves, 0);
+    }
+
+    #[test]
+    fn test_reassign() {
+        assert_eq!(reassign(&[(2, 4), (6, 8)]), Ok(vec![(2, 4), (6, 8)]));
+        assert_eq!(reassign(&[(5, 7), (7, 9)]).map(|new_ranges| shrinkage(&[(5, 7), (7, 9)], &new_ranges)), Ok(1));
+
+        // Nested ranges: one of them keeps the sections of the small one, the other what is left.
+        let ranges = [(2, 9), (3, 7)];
+        assert_eq!(shrinkage(&ranges, &reassign(&ranges).unwrap()), 6);
+        let ranges = [(1, 3), (3, 5), (5, 7)];
+        assert_eq!(shrinkage(&ranges, &reassign(&ranges).unwrap()), 2);
+
+        // The large range keeps 3 sections, or gives 4 to a shrunk neighbour: 7 removed anyway.
+        let ranges = [(1, 10), (2, 3), (6, 7)];
+        assert_eq!(shrinkage(&ranges, &reassign(&ranges).unwrap()), 7);
+
+        assert!(reassign(&[(5, 5), (5, 5)]).is_err());
+        assert!(reassign(&[(4, 3)]).is_err());
+        assert_eq!(reassign(&[(3, 3), (3, 4), (3, 5)]), Ok(vec![(3, 3), (4, 4), (5, 5)]));
+
+        // The new assignments read back with no overlap left.
+        for ranges in read_assignment_lines(BufReader::new(File::open("./data/test.txt").unwrap())) {
+            let new_line = format_elf_ranges(&reassign(&ranges).unwrap());
+            let (first, second) = parse_elf_assignments(&new_line).unwrap();
+            assert!(!AllenRelation::classify(first, second).is_overlap());
+        }
+    }
+
+    #[test]
+    fn global_test() {
+        assert_eq!(execute("./data/test.txt".to_string()), Some((2, 4)));
+    }
+
+    #[test]
+    fn test_allen_relations() {
+        assert_eq!(AllenRelation::classify((2, 4), (6, 8)), AllenRelation::Before);
+        assert_eq!(AllenRelation::classify((2, 3), (4, 5)), AllenRelation::Meets);
+        assert_eq!(AllenRelation::classify((5, 7), (7, 9)), AllenRelation::Overlaps);
+        assert_eq!(AllenRelation::classify((2, 8), (3, 7)), AllenRelation::Contains);
+        assert_eq!(AllenRelation::classify((6, 6), (4, 6)), AllenRelation::Finishes);
+        assert_eq!(AllenRelation::classify((2, 6), (2, 8)), AllenRelation::Starts);
+        assert_eq!(AllenRelation::classify((3, 3), (3, 3)), AllenRelation::Equals);
+
+        // Each relation has its inverse when the pair is swapped.
+        let ranges = [(1, 1), (1, 3), (2, 2), (2, 5), (3, 4), (4, 4), (5, 6), (1, 6)];
+        for &first in &ranges {
+            for &second in &ranges {
+                let relation = AllenRelation::classify(first, second) as usize;
+                assert_eq!(AllenRelation::classify(second, first) as usize, 12 - relation);
+            }
+        }
+
+        let histogram = relation_histogram(&read_assignments(BufReader::new(File::open("./data/test.txt").unwrap())));
+        assert_eq!(histogram, [1, 1, 2, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0]);
+        assert!(draw_histogram(&histogram).starts_with("       Before     1 #"));
+    }
 }
\ No newline at end of file
@@ -4,6 +4,17 @@
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
 use regex::Regex;
+use std::fmt;
+
+// For the planner
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{Hash, Hasher};
+
+// Utility
+use common::cli::DayArgs;
+use common::memory;
 
 
 // Parsing the syntax: "move X from Y to Z"
@@ -26,57 +37,170 @@ fn parse_instruction (input : &str) -> Option<(u32, u32, u32)> {
 }
 
 
-// Applying the movement (for Part 1)
-fn apply_single_movement (from : usize, to: usize, layout: &mut Vec<Vec<char>>) {
+// Why a crane cannot do a move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum MoveError {
+    UnknownStack { stack : u32 },
+    NotEnoughCrates { stack : u32, requested : u32, available : usize },
+    TargetNotShorter { from_height : usize, to_height : usize },
+}
 
-    // Get the last element of column
-    // This is the way I wanted to do it, but the borrowing rules are blocking me. Oh well.
-    // if let Some(val) = layout[from].last() { 
-    //     layout[from].pop();
-    //     layout[to].push(*val)
-    // }
-    let val = layout[from].last().unwrap().clone();
-    layout[from].pop();
-    layout[to].push(val)
+impl fmt::Display for MoveError {
+    fn fmt(&self, formatter : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::UnknownStack { stack } => write!(formatter, "there is no stack {}", stack),
+            MoveError::NotEnoughCrates { stack, requested, available } =>
+                write!(formatter, "stack {} has {} crates, not {}", stack, available, requested),
+            MoveError::TargetNotShorter { from_height, to_height } =>
+                write!(formatter, "a stack of {} cannot be lifted onto a stack of {}", from_height, to_height),
+        }
+    }
 }
 
 
-fn apply_movements (amount: u32, from : usize, to: usize, layout: &mut Vec<Vec<char>>) {
-    for _ in 0..amount {
-        apply_single_movement(from, to, layout);
-    }
+// An impossible instruction, with the line of the input it comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CraneError {
+    line : usize,
+    instruction : (u32, u32, u32),
+    error : MoveError,
 }
 
+impl fmt::Display for CraneError {
+    fn fmt(&self, formatter : &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}, \"move {} from {} to {}\": {}",
+            self.line, self.instruction.0, self.instruction.1, self.instruction.2, self.error)
+    }
+}
 
-// For Part 2.
-fn apply_movements_together (amount: u32, from : usize, to: usize, layout: &mut Vec<Vec<char>>) {
 
-    // retrieving the last N elements
-    let layout_size = layout[from].len();
-    let elements = layout[from].as_slice()[layout[from].len()-(amount as usize)..].to_vec();
+// A model of crane, moving the crates of an instruction "move X from Y to Z".
+// The stacks are numbered from 1. When a move fails the layout can be left half moved.
+trait Crane {
+    fn apply(&self, instruction : (u32, u32, u32), layout : &mut [Vec<char>]) -> Result<(), MoveError>;
+}
 
-    // popping the last elements
-    layout[from].truncate(layout_size - amount as usize);
+// The indices of the two stacks, when they exist and the first one has enough crates.
+fn check_move(instruction : (u32, u32, u32), layout : &[Vec<char>]) -> Result<(usize, usize), MoveError> {
+    let (amount, from, to) = instruction;
+    for stack in [from, to] {
+        if stack == 0 || stack as usize > layout.len() {
+            return Err(MoveError::UnknownStack { stack });
+        }
+    }
+    let available = layout[from as usize - 1].len();
+    if (amount as usize) > available {
+        return Err(MoveError::NotEnoughCrates { stack : from, requested : amount, available });
+    }
+    Ok((from as usize - 1, to as usize - 1))
+}
 
-    // pushing the elements in the other pile:
+// Moving the top crates of a stack together, keeping their order.
+fn lift(amount : usize, from : usize, to : usize, layout : &mut [Vec<char>]) {
+    let from_size = layout[from].len();
+    let elements = layout[from].split_off(from_size - amount);
     layout[to].extend(elements);
 }
 
 
-// Main Function
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 5!");
+// For Part 1: one crate at a time.
+struct CrateMover9000;
 
-    // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
-    let reader = BufReader::new(file);
+impl Crane for CrateMover9000 {
+    fn apply(&self, instruction : (u32, u32, u32), layout : &mut [Vec<char>]) -> Result<(), MoveError> {
+        let (from, to) = check_move(instruction, layout)?;
+        for _ in 0..instruction.0 {
+            lift(1, from, to, layout);
+        }
+        Ok(())
+    }
+}
 
-    // First reading the inputs 
+
+// For Part 2: all the crates at once.
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn apply(&self, instruction : (u32, u32, u32), layout : &mut [Vec<char>]) -> Result<(), MoveError> {
+        let (from, to) = check_move(instruction, layout)?;
+        lift(instruction.0 as usize, from, to, layout);
+        Ok(())
+    }
+}
+
+
+// At most the capacity at once, each lift keeping the order of its crates.
+struct LimitedCrane {
+    capacity : usize,
+}
+
+impl Crane for LimitedCrane {
+    fn apply(&self, instruction : (u32, u32, u32), layout : &mut [Vec<char>]) -> Result<(), MoveError> {
+        let (from, to) = check_move(instruction, layout)?;
+        let mut remaining = instruction.0 as usize;
+        while remaining > 0 {
+            let amount = remaining.min(self.capacity);
+            lift(amount, from, to, layout);
+            remaining -= amount;
+        }
+        Ok(())
+    }
+}
+
+
+// One crate at a time, and only onto a stack shorter than the one it comes from.
+struct ShorterStackCrane;
+
+impl Crane for ShorterStackCrane {
+    fn apply(&self, instruction : (u32, u32, u32), layout : &mut [Vec<char>]) -> Result<(), MoveError> {
+        let (from, to) = check_move(instruction, layout)?;
+        for _ in 0..instruction.0 {
+            let (from_height, to_height) = (layout[from].len(), layout[to].len());
+            if to_height >= from_height {
+                return Err(MoveError::TargetNotShorter { from_height, to_height });
+            }
+            lift(1, from, to, layout);
+        }
+        Ok(())
+    }
+}
+
+
+// The cranes by name: "9000", "9001", "limited:<capacity>" or "shorter".
+fn parse_crane(name : &str) -> Result<Box<dyn Crane>, String> {
+    match name.split_once(':') {
+        None if name == "9000" => Ok(Box::new(CrateMover9000)),
+        None if name == "9001" => Ok(Box::new(CrateMover9001)),
+        None if name == "shorter" => Ok(Box::new(ShorterStackCrane)),
+        Some(("limited", capacity)) => match capacity.parse::<usize>() {
+            Ok(capacity) if capacity > 0 => Ok(Box::new(LimitedCrane { capacity })),
+            _ => Err(format!("the capacity should be a positive number, not '{}'", capacity)),
+        },
+        _ => Err(format!("unknown crane '{}'", name)),
+    }
+}
+
+
+// Applying the instructions in order, the first one being on the given line of the input.
+fn run_instructions(crane : &dyn Crane, crates_instructions : &[(u32, u32, u32)], first_line : usize, layout : &mut [Vec<char>]) -> Result<(), CraneError> {
+    for (index, &instruction) in crates_instructions.iter().enumerate() {
+        crane.apply(instruction, layout).map_err(|error| CraneError { line : first_line + index, instruction, error })?;
+    }
+    Ok(())
+}
+
+
+// The crate on top of each stack, a space for the empty ones.
+fn top_row(layout : &[Vec<char>]) -> String {
+    layout.iter().map(|stack| stack.last().copied().unwrap_or(' ')).collect()
+}
+
+
+// The lines of the diagram and the instructions, the two sections being separated by an empty line.
+fn read_input(reader : impl BufRead) -> (Vec<String>, Vec<(u32, u32, u32)>) {
 
     // Using a vec of vec, since the stacks are always moved from one end.
-    // I gave a peek to the input and decided to make it sized FOR the specific input.
     let mut layout_lines_vec = Vec::<String>::new();
-    let mut crates_layout = Vec::<Vec<char>>::new();
 
     // The order is stored in a tuple: amount of elements, from where, to where.
     let mut crates_instructions = Vec::<(u32, u32, u32)>::new();
@@ -85,85 +209,285 @@ fn main() -> io::Result<()> {
     let mut section_num = 0;
 
     // Finally reading the stuff.
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-            match section_num {
-                0 => {
-                    // The empty line marks the start of the second part of the parsing.
-                    if line.is_empty() {
-                        section_num = 1;
-                        continue;
+    for line in reader.lines().map_while(Result::ok) {
+        match section_num {
+            0 => {
+                // The empty line marks the start of the second part of the parsing.
+                if line.is_empty() {
+                    section_num = 1;
+                    continue;
+                }
+                layout_lines_vec.push(line.to_owned());
+            },
+
+            1 => {
+                // Parsing with a regex.
+                crates_instructions.push(parse_instruction(&line.to_owned()).unwrap());
+            },
+            _ => panic!("There should be only two sections!"),
+        }
+    }
+    (layout_lines_vec, crates_instructions)
+}
+
+
+// Reading the diagram of the stacks, the last line being the index row numbering them
+// from 1. The crates of a stack are in the column of the last digit of its number, so
+// the numbers can have several digits; a crate elsewhere or over an empty slot is an error.
+fn parse_layout(layout_lines : &[String]) -> Result<Vec<Vec<char>>, String> {
+    let Some((index_row, crate_rows)) = layout_lines.split_last() else {
+        return Err("the diagram has no index row".to_string());
+    };
+    let label_regex = Regex::new(r"\S+").unwrap();
+    let mut columns = Vec::<usize>::new();
+    for label in label_regex.find_iter(index_row) {
+        if label.as_str().parse::<usize>() != Ok(columns.len() + 1) {
+            return Err(format!("the index row should number the stacks from 1, found '{}'", label.as_str()));
+        }
+        columns.push(label.end() - 1);
+    }
+    if columns.is_empty() {
+        return Err("the index row has no stack".to_string());
+    }
+
+    // Reading the rows from the bottom.
+    let mut crates_layout = vec![Vec::<char>::new(); columns.len()];
+    for (height, line) in crate_rows.iter().rev().enumerate() {
+        let line_number = crate_rows.len() - height;
+        let characters : Vec<char> = line.chars().collect();
+        if let Some(position) = (0..characters.len()).find(|&position| characters[position] == '[' && !columns.contains(&(position + 1))) {
+            return Err(format!("line {}: the crate at column {} is not under a stack number", line_number, position + 1));
+        }
+        for (stack_idx, &column) in columns.iter().enumerate() {
+            match characters.get(column).copied().unwrap_or(' ') {
+                ' ' => (),
+                '[' | ']' => return Err(format!("line {}: the crate of stack {} is not aligned", line_number, stack_idx + 1)),
+                crate_name => {
+                    if crates_layout[stack_idx].len() != height {
+                        return Err(format!("line {}: the crate {} of stack {} floats over an empty slot", line_number, crate_name, stack_idx + 1));
                     }
-                    layout_lines_vec.push(line.to_owned());
+                    crates_layout[stack_idx].push(crate_name);
                 },
+            }
+        }
+    }
+    Ok(crates_layout)
+}
 
-                1 => {
-                    // Parsing with a regex.
-                    crates_instructions.push(parse_instruction(&line.to_owned()).unwrap());
-                },
-                _ => panic!("There should be only two sections!"),
+
+// Drawing the stacks the way the puzzle does, the index row included.
+fn render_layout(crates_layout : &[Vec<char>]) -> String {
+    let height = crates_layout.iter().map(|stack| stack.len()).max().unwrap_or(0);
+    let mut diagram = String::new();
+    for level in (0..height).rev() {
+        let cells : Vec<String> = crates_layout.iter()
+            .map(|stack| stack.get(level).map_or("   ".to_string(), |crate_name| format!("[{}]", crate_name)))
+            .collect();
+        diagram += &cells.join(" ");
+        diagram += "\n";
+    }
+
+    // Each label ends on the column of its crates, whatever its number of digits.
+    let mut index_row = vec![' '; (4 * crates_layout.len()).saturating_sub(1)];
+    for stack_idx in 0..crates_layout.len() {
+        let label : Vec<char> = (stack_idx + 1).to_string().chars().collect();
+        let start = 4 * stack_idx + 2 - label.len();
+        index_row[start..start + label.len()].copy_from_slice(&label);
+    }
+    diagram += &index_row.into_iter().collect::<String>();
+    diagram += "\n";
+    diagram
+}
+
+
+// Writing the syntax: "move X from Y to Z"
+fn format_instruction(instruction : (u32, u32, u32)) -> String {
+    format!("move {} from {} to {}", instruction.0, instruction.1, instruction.2)
+}
+
+
+// Over this many layouts stored, the planner gives up. Each expansion stores up to
+// every move of the crane, so the limit is on them rather than on the expansions.
+const PLAN_MAX_LAYOUTS : usize = 200_000;
+
+// The stacks the target asks for that don't have the right top yet. In the target
+// a '.' accepts any crate and a space asks for an empty stack.
+fn misplaced_tops(layout : &[Vec<char>], target : &[char]) -> usize {
+    layout.iter().zip(target)
+        .filter(|(stack, &wanted)| wanted != '.' && stack.last().copied().unwrap_or(' ') != wanted)
+        .count()
+}
+
+
+fn layout_hash(layout : &[Vec<char>]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    layout.hash(&mut hasher);
+    hasher.finish()
+}
+
+
+// A short list of instructions giving the target top row with the crane. The search is
+// a best-first one over the layouts, the ones closer to the target and reached with fewer
+// moves first; every move the crane accepts is tried, so the plan is short but not
+// always the shortest.
+fn plan_rearrangement(crane : &dyn Crane, crates_layout : &[Vec<char>], target : &str) -> Result<Vec<(u32, u32, u32)>, String> {
+    let target : Vec<char> = target.chars().collect();
+    if target.len() != crates_layout.len() {
+        return Err(format!("the target has {} stacks, the layout {}", target.len(), crates_layout.len()));
+    }
+    let mut available = HashMap::<char, usize>::new();
+    for &crate_name in crates_layout.iter().flatten() {
+        *available.entry(crate_name).or_default() += 1;
+    }
+    for &wanted in target.iter().filter(|&&wanted| wanted != '.' && wanted != ' ') {
+        let count = available.entry(wanted).or_default();
+        if *count == 0 {
+            return Err(format!("there are not enough crates {} for the target", wanted));
+        }
+        *count -= 1;
+    }
+    if target.iter().all(|&wanted| wanted == ' ') && available.values().any(|&count| count > 0) {
+        return Err("the crates cannot all be removed from the stacks".to_string());
+    }
+
+    // Each layout reached, with the layout it comes from and the instruction leading to it.
+    // The layouts are only stored there, the ones already reached are found by their hash.
+    let mut layouts = vec![(crates_layout.to_vec(), usize::MAX, (0, 0, 0))];
+    let mut seen = HashMap::<u64, Vec<usize>>::from([(layout_hash(crates_layout), vec![0])]);
+    let mut queue = BinaryHeap::from([Reverse((2 * misplaced_tops(crates_layout, &target), 0, 0))]);
+    while let Some(Reverse((_, moves, index))) = queue.pop() {
+        if misplaced_tops(&layouts[index].0, &target) == 0 {
+            let mut instructions = Vec::<(u32, u32, u32)>::new();
+            let mut current = index;
+            while layouts[current].1 != usize::MAX {
+                instructions.push(layouts[current].2);
+                current = layouts[current].1;
+            }
+            instructions.reverse();
+            return Ok(instructions);
+        }
+        for from in 0..crates_layout.len() {
+            for to in (0..crates_layout.len()).filter(|&to| to != from) {
+                for amount in 1..=layouts[index].0[from].len() {
+                    let instruction = (amount as u32, from as u32 + 1, to as u32 + 1);
+                    let mut next_layout = layouts[index].0.clone();
+                    if crane.apply(instruction, &mut next_layout).is_err() {
+                        continue;
+                    }
+                    if layouts.len() == PLAN_MAX_LAYOUTS {
+                        return Err(format!("no plan found among the first {} layouts", PLAN_MAX_LAYOUTS));
+                    }
+                    let same_hash = seen.entry(layout_hash(&next_layout)).or_default();
+                    if same_hash.iter().any(|&reached| layouts[reached].0 == next_layout) {
+                        continue;
+                    }
+                    same_hash.push(layouts.len());
+                    queue.push(Reverse((moves + 1 + 2 * misplaced_tops(&next_layout, &target), moves + 1, layouts.len())));
+                    layouts.push((next_layout, index, instruction));
+                }
             }
         }
     }
+    Err(format!("no plan found among the {} reachable layouts", layouts.len()))
+}
+
+
+// Primary Function
+fn execute (input_path : String, draw_mode : bool)  -> Option<(String, String)> {
+
+    // Handling the reading/parsing
+    let file = File::open(input_path).unwrap();
+    let reader = BufReader::new(file);
+
+    // First reading the inputs 
+    let (layout_lines_vec, crates_instructions) = read_input(reader);
+    let first_instruction_line = layout_lines_vec.len() + 2;
 
     // Interpreting the layout.
-    // Reading the vector from the bottom - the first line is a counter of the elements.
-    let mut layout_iter = layout_lines_vec.into_iter().rev();
-    let first_line = layout_iter.next().unwrap();
-    let temp_vec: Vec<&str> = first_line.split("   ").collect();
-    let stacks_number = temp_vec.len();
+    // Reading the vector from the bottom - the last line is a counter of the elements.
+    let crates_layout = parse_layout(&layout_lines_vec).unwrap_or_else(|message| panic!("Wrong diagram: {}", message));
+    let stacks_number = crates_layout.len();
     println!("there are {} stacks of crates", stacks_number);
-    crates_layout.resize(stacks_number, Vec::<char>::new());
-    while let Some(item) = layout_iter.next() {
-        for stack_idx in 0..stacks_number {
-            
-            // Extracting the character if it exists:
-            let selected_character = item.chars().nth((1 + 4 * (stack_idx as u32)).try_into().unwrap()).unwrap();
-            if selected_character != ' ' {
-                crates_layout[stack_idx].push(selected_character);
-            } 
-        }
-    }
 
     // Copying the layout for the processing
     let mut crates_layout_part_1 = crates_layout.clone();
 
     // Iterating over the instructions:
-    for element in &crates_instructions {
-        apply_movements(
-            element.0, //amount
-            TryInto::<usize>::try_into(element.1 - 1).unwrap(), // from 
-            TryInto::<usize>::try_into(element.2 - 1).unwrap(), // to
-            &mut crates_layout_part_1
-        )
+    if let Err(error) = run_instructions(&CrateMover9000, &crates_instructions, first_instruction_line, &mut crates_layout_part_1) {
+        println!("The CrateMover 9000 stops at {}.", error);
+        return None;
     }
 
     // Extracting the last element from each stack:
-    let mut part_1_result : String = "".to_owned();
-    for stack_idx in 0..stacks_number {
-        part_1_result.push(crates_layout_part_1[stack_idx].last().unwrap().to_owned());
+    if draw_mode {
+        print!("{}", render_layout(&crates_layout_part_1));
     }
-    println!("\nPart 1 Result is {}.", part_1_result);
+    let result_part_1 = top_row(&crates_layout_part_1);
+    memory::report_part(1);
 
     // For Part 2 the crane is capable of moving MULTIPLE crates at once.
     // Iterating over the instructions:
     let mut crates_layout_part_2 = crates_layout.clone();
-    for element in &crates_instructions {
-        //println!("Applying movements: {} from {} to {}", element.0, element.1, element.2);
-        apply_movements_together(
-            element.0, //amount
-            TryInto::<usize>::try_into(element.1 - 1).unwrap(), // from 
-            TryInto::<usize>::try_into(element.2 - 1).unwrap(), // to
-            &mut crates_layout_part_2
-        );
+    if let Err(error) = run_instructions(&CrateMover9001, &crates_instructions, first_instruction_line, &mut crates_layout_part_2) {
+        println!("The CrateMover 9001 stops at {}.", error);
+        return None;
     }
 
     // Extracting the last element from each stack:
-    let mut part_2_result : String = "".to_owned();
-    for stack_idx in 0..stacks_number {
-        part_2_result.push(crates_layout_part_2[stack_idx].last().unwrap().to_owned());
+    if draw_mode {
+        print!("{}", render_layout(&crates_layout_part_2));
     }
-    println!("\nPart 2 Result is {}.", part_2_result);
+    let result_part_2 = top_row(&crates_layout_part_2);
+
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
+
+
+// Main 
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 5!");
+
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // With --draw the stacks are drawn after the moves of each part. Another crane can
+    // follow the instructions with --crane <9000|9001|limited:N|shorter>. With
+    // --plan <top crates> <crane> the instructions giving those top crates are searched,
+    // a '.' accepting any crate.
+    let mut args = DayArgs::from_env();
+    let draw_mode = args.take_switch("--draw");
+    let crane_option = args.take_option("--crane", 1);
+    let plan_option = args.take_option("--plan", 2);
+    let input_path = args.input_path("./data/input.txt");
+    if let Some(values) = plan_option {
+        let crane = parse_crane(&values[1]).unwrap_or_else(|message| panic!("Wrong crane: {}", message));
+        let (layout_lines_vec, _) = read_input(BufReader::new(File::open(&input_path)?));
+        let crates_layout = parse_layout(&layout_lines_vec).unwrap_or_else(|message| panic!("Wrong diagram: {}", message));
+        match plan_rearrangement(crane.as_ref(), &crates_layout, &values[0]) {
+            Ok(instructions) => {
+                for &instruction in &instructions {
+                    println!("{}", format_instruction(instruction));
+                }
+                println!("{} instructions give the top crates '{}' with the crane {}.", instructions.len(), values[0], values[1]);
+            },
+            Err(message) => println!("Cannot plan the top crates '{}': {}.", values[0], message),
+        }
+    }
+    if let Some(values) = crane_option {
+        let crane = parse_crane(&values[0]).unwrap_or_else(|message| panic!("Wrong crane: {}", message));
+        let (layout_lines_vec, crates_instructions) = read_input(BufReader::new(File::open(&input_path)?));
+        let mut crates_layout = parse_layout(&layout_lines_vec).unwrap_or_else(|message| panic!("Wrong diagram: {}", message));
+        match run_instructions(crane.as_ref(), &crates_instructions, layout_lines_vec.len() + 2, &mut crates_layout) {
+            Ok(()) => println!("With the crane {} the top crates are {}.", values[0], top_row(&crates_layout)),
+            Err(error) => println!("The crane {} stops at {}.", values[0], error),
+        }
+    }
+    // An impossible instruction has already been reported, with its line.
+    let Some(results) = execute(input_path, draw_mode) else {
+        std::process::exit(1);
+    };
+    
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
@@ -179,6 +503,114 @@ mod tests {
         assert_eq!(parse_instruction("move 3 from 2 to 5").unwrap(), (3,2,5));
     }
 
-    // I should have added more tests, but setting up the "layout" of the crates 
-    // would have been too time-consuming.
+    #[test]
+    fn global_test() {
+        assert_eq!(execute("./data/test.txt".to_string(), false), Some(("CMZ".to_string(), "MCD".to_string())));
+    }
+
+    #[test]
+    fn test_cranes() {
+        let layout = vec![vec!['A', 'B', 'C', 'D'], vec!['E'], vec![]];
+        let moved = |crane : &dyn Crane, instruction| {
+            let mut layout = layout.clone();
+            crane.apply(instruction, &mut layout).map(|_| layout)
+        };
+        assert_eq!(moved(&CrateMover9000, (3, 1, 3)).unwrap()[2], vec!['D', 'C', 'B']);
+        assert_eq!(moved(&CrateMover9001, (3, 1, 3)).unwrap()[2], vec!['B', 'C', 'D']);
+        assert_eq!(moved(&LimitedCrane { capacity : 2 }, (3, 1, 3)).unwrap()[2], vec!['C', 'D', 'B']);
+        assert_eq!(moved(&LimitedCrane { capacity : 1 }, (4, 1, 2)).unwrap()[1], vec!['E', 'D', 'C', 'B', 'A']);
+
+        // Onto the empty stack, a stack of 4 then 3 can take a crate, not a stack of 2.
+        assert_eq!(moved(&ShorterStackCrane, (2, 1, 3)).unwrap()[2], vec!['D', 'C']);
+        assert_eq!(moved(&ShorterStackCrane, (3, 1, 3)), Err(MoveError::TargetNotShorter { from_height : 2, to_height : 2 }));
+        assert_eq!(moved(&ShorterStackCrane, (1, 2, 1)), Err(MoveError::TargetNotShorter { from_height : 1, to_height : 4 }));
+
+        assert_eq!(moved(&CrateMover9001, (2, 2, 1)), Err(MoveError::NotEnoughCrates { stack : 2, requested : 2, available : 1 }));
+        assert_eq!(moved(&CrateMover9000, (1, 4, 1)), Err(MoveError::UnknownStack { stack : 4 }));
+        assert_eq!(moved(&CrateMover9000, (1, 1, 0)), Err(MoveError::UnknownStack { stack : 0 }));
+
+        // The error names the line of the instruction.
+        let mut layout = layout.clone();
+        let error = run_instructions(&CrateMover9000, &[(1, 2, 3), (1, 2, 3)], 7, &mut layout).unwrap_err();
+        assert_eq!(error.to_string(), "line 8, \"move 1 from 2 to 3\": stack 2 has 0 crates, not 1");
+
+        assert!(parse_crane("limited:3").is_ok());
+        assert!(parse_crane("limited:0").is_err());
+        assert!(parse_crane("9002").is_err());
+        assert_eq!(top_row(&layout), "D E");
+    }
+
+    #[test]
+    fn test_plan_rearrangement() {
+        let crates_layout = parse_layout(&diagram_lines("./data/test.txt")).unwrap();
+
+        // The plans replay through the instruction parser.
+        let replay = |crane : &dyn Crane, instructions : &[(u32, u32, u32)]| {
+            let instructions : Vec<(u32, u32, u32)> = instructions.iter()
+                .map(|&instruction| parse_instruction(&format_instruction(instruction)).unwrap())
+                .collect();
+            let mut layout = crates_layout.clone();
+            run_instructions(crane, &instructions, 1, &mut layout).unwrap();
+            top_row(&layout)
+        };
+        let cranes : [(&dyn Crane, &str); 4] = [
+            (&CrateMover9000, "ZMN"), (&CrateMover9001, "CZD"), (&LimitedCrane { capacity : 2 }, "MN "), (&ShorterStackCrane, "N.."),
+        ];
+        for (crane, target) in cranes {
+            let instructions = plan_rearrangement(crane, &crates_layout, target).unwrap();
+            let top = replay(crane, &instructions);
+            assert!(top.chars().zip(target.chars()).all(|(top, wanted)| wanted == '.' || top == wanted), "{} instead of {}", top, target);
+        }
+
+        // Nothing to move for the current top row, one move uncovers Z.
+        assert_eq!(plan_rearrangement(&CrateMover9000, &crates_layout, "NDP").unwrap(), vec![]);
+        assert_eq!(plan_rearrangement(&CrateMover9001, &crates_layout, "Z..").unwrap().len(), 1);
+
+        assert!(plan_rearrangement(&CrateMover9000, &crates_layout, "XDP").is_err());
+        assert!(plan_rearrangement(&CrateMover9000, &crates_layout, "DDP").is_err());
+        assert!(plan_rearrangement(&CrateMover9000, &crates_layout, "ND").is_err());
+
+        // Two single crates cannot be swapped onto stacks as tall, only the start is reachable.
+        assert_eq!(plan_rearrangement(&ShorterStackCrane, &[vec!['A'], vec!['B']], "BA"),
+            Err("no plan found among the 1 reachable layouts".to_string()));
+        assert!(plan_rearrangement(&CrateMover9001, &crates_layout, "   ").is_err());
+    }
+
+    fn diagram_lines(path : &str) -> Vec<String> {
+        let text = std::fs::read_to_string(path).unwrap();
+        text.lines().take_while(|line| !line.is_empty()).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_layout() {
+        let crates_layout = parse_layout(&diagram_lines("./data/test.txt")).unwrap();
+        assert_eq!(crates_layout, vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]);
+
+        // Drawing the parsed diagrams gives them back.
+        for path in ["./data/test.txt", "./data/input.txt", "./data/test_wide.txt"] {
+            let lines = diagram_lines(path);
+            let diagram = lines.iter().map(|line| format!("{}\n", line)).collect::<String>();
+            assert_eq!(render_layout(&parse_layout(&lines).unwrap()), diagram);
+        }
+        assert_eq!(parse_layout(&diagram_lines("./data/test_wide.txt")).unwrap()[10], vec!['K', 'L']);
+
+        // The trailing spaces can be trimmed, the drawing always pads the rows to the last stack.
+        let trimmed : Vec<String> = diagram_lines("./data/test.txt").iter().map(|line| line.trim_end().to_string()).collect();
+        assert_eq!(trimmed[3], " 1   2   3");
+        assert_eq!(parse_layout(&trimmed).unwrap(), crates_layout);
+        assert_eq!(render_layout(&crates_layout), "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n");
+
+        // From stack 100 on the labels take three columns, still ending on the crates.
+        let many_stacks : Vec<Vec<char>> = (0..120).map(|stack_idx| vec![(b'A' + stack_idx % 26) as char]).collect();
+        let diagram = render_layout(&many_stacks);
+        assert!(diagram.ends_with(" 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114 115 116 117 118 119 120 \n"));
+        assert_eq!(parse_layout(&diagram.lines().map(str::to_string).collect::<Vec<_>>()).unwrap(), many_stacks);
+
+        let wrong = |diagram : &str| parse_layout(&diagram.lines().map(str::to_string).collect::<Vec<_>>());
+        assert!(wrong("").is_err());
+        assert!(wrong("[A]\n 2 ").is_err());
+        assert!(wrong("  [A]\n 1   2 ").is_err());
+        assert!(wrong("[A]    \n    [B]\n 1   2 ").is_err());
+        assert!(wrong("[A] [B]\n 1   2 ").is_ok());
+    }
 }
\ No newline at end of file
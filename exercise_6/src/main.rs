@@ -3,6 +3,10 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
+
+// Memory accounting
+use common::memory;
 
 
 fn check_no_duplicates_in_slice(input_slice : &[u8]) -> bool {
@@ -16,21 +20,18 @@ fn check_no_duplicates_in_slice(input_slice : &[u8]) -> bool {
 }
 
 
-// Main Function
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 6!");
+// Primary Function
+fn execute (input_path : String)  -> Option<(usize, usize)> {
 
     // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
+    let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
     // First reading the input string - easy.
     let mut input_line : String = "".to_string();
     // Finally reading the stuff.
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-            input_line = line;
-        }
+    for line in reader.lines().map_while(Result::ok) {
+        input_line = line;
     }
     println!("read {} characters from input", input_line.len());
 
@@ -42,31 +43,50 @@ fn main() -> io::Result<()> {
     // I'm gonna use the first, with the slices.
     let input_line = input_line.as_bytes();
     let window_size = 4;
-    let mut moving_window = input_line.windows(window_size);
+    let moving_window = input_line.windows(window_size);
 
     // Iterating as long as the "next" works well
     // Note that this might give weird results at the *end* of the string, 
     // I don't know the exact behaviour of windows at the end of the vector.
     let mut iteration_counter = 0;
-    while let Some(element) = moving_window.next() {
+    for element in moving_window {
         if check_no_duplicates_in_slice(element) {
-            println!("found 4 unique elements at iteration {}, result for Part 1 is {}", iteration_counter, iteration_counter + window_size);
+            println!("found 4 unique elements at iteration {}", iteration_counter);
             break;
         }
         iteration_counter += 1;
     }
+    let result_part_1 = iteration_counter + window_size;
+    memory::report_part(1);
 
     // Part 2 - Same but with 14 elements.
     let window_size = 14;
-    let mut moving_window = input_line.windows(window_size);
+    let moving_window = input_line.windows(window_size);
     let mut iteration_counter = 0;
-    while let Some(element) = moving_window.next() {
+    for element in moving_window {
         if check_no_duplicates_in_slice(element) {
-            println!("found 14 unique elements at iteration {}, result for Part 2 is {}", iteration_counter, iteration_counter + window_size);
+            println!("found 14 unique elements at iteration {}", iteration_counter);
             break;
         }
         iteration_counter += 1;
     }
+    let result_part_2 = iteration_counter + window_size;
+
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
+
+
+// Main 
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 6!");
+
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
+    
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
@@ -79,9 +99,9 @@ mod tests {
 
     #[test]
     fn test_check_duplicates() {
-        assert_eq!(check_no_duplicates_in_slice("awrrhjyj".as_bytes()), false);
-        assert_eq!(check_no_duplicates_in_slice("a".as_bytes()), true);
-        assert_eq!(check_no_duplicates_in_slice("abcdefghi".as_bytes()), true);
-        assert_eq!(check_no_duplicates_in_slice("aAbBcCdD".as_bytes()), true);
+        assert!(!check_no_duplicates_in_slice("awrrhjyj".as_bytes()));
+        assert!(check_no_duplicates_in_slice("a".as_bytes()));
+        assert!(check_no_duplicates_in_slice("abcdefghi".as_bytes()));
+        assert!(check_no_duplicates_in_slice("aAbBcCdD".as_bytes()));
     }
 }
\ No newline at end of file
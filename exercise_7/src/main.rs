@@ -3,6 +3,10 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
+
+// Memory accounting
+use common::memory;
 
 // For handles
 use std::rc::Rc;
@@ -34,7 +38,7 @@ impl Folder {
             total_size += file.1;
         }
         
-        for (_, subfolder) in &self.subfolders {
+        for subfolder in self.subfolders.values() {
             total_size += subfolder.borrow_mut().get_size();
         }
         total_size
@@ -56,38 +60,35 @@ fn get_line_command(input_line : String) -> LineCommands {
 
     // matching the start of the string:
     match input_line.as_str() {
-        "$ cd /" => return LineCommands::GoToRoot,
-        "$ cd .." => return LineCommands::GoToParent,
+        "$ cd /" => LineCommands::GoToRoot,
+        "$ cd .." => LineCommands::GoToParent,
         line if line.contains("$ cd ") => {
-            return LineCommands::GoToFolder(input_line.strip_prefix("$ cd ").unwrap().to_string())
+            LineCommands::GoToFolder(input_line.strip_prefix("$ cd ").unwrap().to_string())
         },
         line if line.contains("dir ") => {
-            return LineCommands::AddFolder(input_line.strip_prefix("dir ").unwrap().to_string())
+            LineCommands::AddFolder(input_line.strip_prefix("dir ").unwrap().to_string())
         },
         line if line.split(' ').next().is_some() && line.split(' ').next().unwrap().parse::<i32>().is_ok() => {
-            return LineCommands::AddFile((line.split(' ').nth(1).unwrap().to_string(), line.split(' ').nth(0).unwrap().parse::<u32>().unwrap()))
+            LineCommands::AddFile((line.split(' ').nth(1).unwrap().to_string(), line.split(' ').next().unwrap().parse::<u32>().unwrap()))
         },
-        "$ ls" => return LineCommands::Ignore,
+        "$ ls" => LineCommands::Ignore,
         _ => panic!("Command not recognized: '{}'", input_line),
     }
 }
 
 
-// Main Function
-fn main() -> io::Result<()> {
-    println!("Welcome to Advent of Code 2022 - Day 7!");
+// Primary Function
+fn execute (input_path : String)  -> Option<(u32, u32)> {
 
     // Handling the reading/parsing
-    let file = File::open("./data/input.txt")?;
+    let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
     // First reading the input string - easy.
     let mut commands_vec = Vec::<String>::new();
     // Finally reading the stuff.
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-            commands_vec.push(line);
-        }
+    for line in reader.lines().map_while(Result::ok) {
+        commands_vec.push(line);
     }
     println!("read {} lines from input", commands_vec.len());
 
@@ -131,7 +132,7 @@ fn main() -> io::Result<()> {
                 }
             },
             LineCommands::AddFile((file_name, file_size)) => {
-                current_cursor.borrow_mut().files.insert(file_name.clone(), file_size.clone());
+                current_cursor.borrow_mut().files.insert(file_name.clone(), file_size);
             },
             LineCommands::Ignore => {},
         }
@@ -148,7 +149,8 @@ fn main() -> io::Result<()> {
             total_small_folders_sum += folder_size;
         }
     }
-    println!("The result of Part 1 is {}.", total_small_folders_sum);
+    let result_part_1 = total_small_folders_sum;
+    memory::report_part(1);
 
     // For part 2 I must find the smallest folder greater or equal to 8381165.
     // I'm gonna go with a blunt approach.
@@ -165,7 +167,23 @@ fn main() -> io::Result<()> {
             chosen_folder_size = folder_size;
         }
     }
-    println!("The result of Part 2 is {}.", chosen_folder_size);
+    let result_part_2 = chosen_folder_size;
+
+    memory::report_part(2);
+    Some((result_part_1, result_part_2))
+}
+
+
+// Main 
+fn main() -> io::Result<()> {
+    println!("Welcome to Advent of Code 2022 - Day 7!");
+
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
+    
+    println!("Part 1 result is {}.", results.0);
+    println!("Part 2 result is {}.", results.1);
 
     // End of main
     Ok(())
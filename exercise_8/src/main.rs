@@ -3,42 +3,36 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
 
 // utility
 use std::cmp;
-
-// Index from coordinates:
-fn index_from_coords (i_coords : &(usize, usize), i_rows_number : &usize) -> Option<usize> {
-    Some(i_coords.0 + i_coords.1 * i_rows_number)
-}
+use common::geometry::{Dir4, Point2};
+use common::memory;
 
 // Check visibility along one direction:
 fn is_visibile_along (
         i_data : &Vec::<u8>, 
-        i_coords : &(usize, usize),
-        i_matrix_size : &(usize, usize),
-        i_movement : &dyn Fn((usize, usize)) -> Option<(usize, usize)>) -> bool {
+        i_coords : &Point2,
+        i_matrix_size : &Point2,
+        i_direction : Dir4) -> bool {
 
-    // Setting the current cursor and then moving it giving the function passed as input
-    let tree_height = i_data[index_from_coords(&i_coords, &i_matrix_size.0).unwrap()];
-    let mut current_position = i_coords.clone();
-    for _ in 0..cmp::max(i_matrix_size.0, i_matrix_size.1) {
+    // Setting the current cursor and then moving it along the given direction
+    let tree_height = i_data[i_coords.index_in(i_matrix_size).unwrap()];
+    let mut current_position = *i_coords;
+    for _ in 0..cmp::max(i_matrix_size.x, i_matrix_size.y) {
 
         // Move to new position, check if taller, return if it is.
-        let new_position = i_movement(current_position);
-        if new_position.is_none() {
-            return true;
-        }
-        let new_position = new_position.unwrap();
+        let new_position = current_position.step(i_direction);
 
         // If reached the border the visibility is OK
-        if new_position.0 >= i_matrix_size.0 || 
-        new_position.1 >= i_matrix_size.1 {
-            return true;
-        }
+        let new_index = match new_position.index_in(i_matrix_size) {
+            Some(index) => index,
+            None => return true,
+        };
 
         // Checking if the element is shorter.
-        if tree_height <= i_data[index_from_coords(&new_position, &i_matrix_size.0).unwrap()] {
+        if tree_height <= i_data[new_index] {
             return false;
         }
 
@@ -51,31 +45,27 @@ fn is_visibile_along (
 // For part 2, calculate the view distance along directions
 fn get_view_distance  (
     i_data : &Vec::<u8>, 
-    i_coords : &(usize, usize),
-    i_matrix_size : &(usize, usize),
-    i_movement : &dyn Fn((usize, usize)) -> Option<(usize, usize)>) -> u32 {
+    i_coords : &Point2,
+    i_matrix_size : &Point2,
+    i_direction : Dir4) -> u32 {
 
     // As before, moving along the direction until either an ending has been reached or a tall tree.
-    let tree_height = i_data[index_from_coords(&i_coords, &i_matrix_size.0).unwrap()];
-    let mut current_position = i_coords.clone();
+    let tree_height = i_data[i_coords.index_in(i_matrix_size).unwrap()];
+    let mut current_position = *i_coords;
     let mut view_distance = 0;
-    for _ in 0..cmp::max(i_matrix_size.0, i_matrix_size.1) {
+    for _ in 0..cmp::max(i_matrix_size.x, i_matrix_size.y) {
 
         // Move to new position, check if taller, return if it is.
-        let new_position = i_movement(current_position);
-        if new_position.is_none() {
-            return view_distance;
-        }
-        let new_position = new_position.unwrap();
+        let new_position = current_position.step(i_direction);
 
         // If reached the border the visibility is OK
-        if new_position.0 >= i_matrix_size.0 || 
-        new_position.1 >= i_matrix_size.1 {
-            return view_distance;
-        }
+        let new_index = match new_position.index_in(i_matrix_size) {
+            Some(index) => index,
+            None => return view_distance,
+        };
 
         // Checking if the element is shorter.
-        if tree_height <= i_data[index_from_coords(&new_position, &i_matrix_size.0).unwrap()] {
+        if tree_height <= i_data[new_index] {
             return view_distance + 1; // The tree in view is part of it.
         }
 
@@ -86,27 +76,6 @@ fn get_view_distance  (
     view_distance
 }
 
-// Movements functions:
-// Note that coordinates start from top-right.
-fn go_up (i_pos : (usize, usize)) -> Option<(usize, usize)> {
-    if i_pos.1 == 0 {
-        return None
-    }
-    Some((i_pos.0, i_pos.1 - 1))
-}
-fn go_down (i_pos : (usize, usize)) -> Option<(usize, usize)> {
-    Some((i_pos.0, i_pos.1 + 1))
-}
-fn go_left (i_pos : (usize, usize)) -> Option<(usize, usize)> {
-    if i_pos.0 == 0 {
-        return None
-    }
-    Some((i_pos.0 - 1, i_pos.1))
-}
-fn go_right (i_pos : (usize, usize)) -> Option<(usize, usize)> {
-    Some((i_pos.0 + 1, i_pos.1))
-}
-
 // Primary Function
 fn execute (input_path : String)  -> Option<(u32, u32)> {
 
@@ -142,40 +111,36 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 
     // Checking tree height for each position from each direction.
     // Looks like the four directions require a bit of redundant code.
+    // With the directions enum the four checks become a loop.
+    let matrix_size = Point2::from_usize(cols_number, rows_number);
     let mut visible_trees_counter = 0;
     for col_idx in 0..cols_number {
         for row_idx in 0..rows_number {
-            if is_visibile_along(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_right) {
-                visible_trees_counter += 1;
-            }
-            else if is_visibile_along(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_left) {
-                visible_trees_counter += 1;
-            }
-            else if is_visibile_along(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_up) {
-                visible_trees_counter += 1;
-            }
-            else if is_visibile_along(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_down) {
+            let coords = Point2::from_usize(col_idx, row_idx);
+            if Dir4::ALL.iter().any(|&direction| is_visibile_along(&all_trees_matrix, &coords, &matrix_size, direction)) {
                 visible_trees_counter += 1;
             }
         }
     }
     result_part_1 = visible_trees_counter;
+    memory::report_part(1);
 
     // For part 2, checking all the view distances: the score is a product of all four.
     // The result is the higher "scenic score" among all trees.
     let mut scenic_scores = Vec::<u32>::new();
     for col_idx in 0..cols_number {
         for row_idx in 0..rows_number {
-            let mut scenic_score = get_view_distance(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_right);
-            scenic_score *= get_view_distance(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_left); 
-            scenic_score *= get_view_distance(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_up); 
-            scenic_score *= get_view_distance(&all_trees_matrix, &(col_idx, row_idx), &(cols_number, rows_number), &go_down);
+            let coords = Point2::from_usize(col_idx, row_idx);
+            let scenic_score = Dir4::ALL.iter()
+                .map(|&direction| get_view_distance(&all_trees_matrix, &coords, &matrix_size, direction))
+                .product();
             scenic_scores.push(scenic_score);
         }
     }
     result_part_2 = scenic_scores.iter().max().unwrap_or(&0).clone();
 
     // Returning both results.
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -184,7 +149,9 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 8!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -3,118 +3,198 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::collections::HashSet;
 
 //Utility
-use std::cmp;
-
-// Handling the directions
-enum Directions {
-    U,
-    D,
-    L,
-    R,
-}
+use common::cli::DayArgs;
+use common::geometry::{Dir4, Point2};
+use common::memory;
+use common::stepper::{self, Steppable};
 
 
 // Given the positions of head and tail, moving multiple times along segment with the head to follow.
-fn move_segment(positions : &((i32, i32), (i32, i32)), direction: &Directions) -> ((i32, i32), (i32, i32)) {
-    let mut out_positions = positions.clone();
-    out_positions.0 = move_head(&out_positions.0, direction);
-    out_positions.1 = follow_head(&out_positions);
-    out_positions
+fn move_segment(positions : &(Point2, Point2), direction: Dir4) -> (Point2, Point2) {
+    let head = move_head(&positions.0, direction);
+    (head, follow_head(&(head, positions.1)))
 }
 
 
-// Basic movement, but wraps the enum match.
-fn move_head(position: &(i32, i32), direction: &Directions) -> (i32, i32) {
-
-    match direction {
-        &Directions::U => return (position.0, position.1 + 1),
-        &Directions::D => return (position.0, position.1 - 1),
-        &Directions::L => return (position.0 - 1, position.1),
-        &Directions::R => return (position.0 + 1, position.1),
-    }
+// Basic movement. The rope lives on a cartesian plane, so "U" increases y.
+fn move_head(position: &Point2, direction: Dir4) -> Point2 {
+    *position + direction.delta_y_up()
 }
 
 
 // Given the positions of head and tail, moving the tail to follow the head.
-fn follow_head(positions : &((i32, i32), (i32, i32))) -> (i32, i32)
+fn follow_head(positions : &(Point2, Point2)) -> Point2
 {
     // Check if already in contact:
-    let x_distance = positions.0.0 - positions.1.0;
-    let y_distance = positions.0.1 - positions.1.1;
-    if cmp::max(x_distance.abs(), y_distance.abs()) <= 1 {
+    if positions.0.chebyshev(&positions.1) <= 1 {
         
         // The tail is already in a comfortable position.
         return positions.1;
     }
 
     // Tail follows head in order to be always in contact in the closest way.
-    let mut tail_pos = positions.1.clone();
-    if x_distance.abs() > 0 {
-        tail_pos.0 += (x_distance).signum();
-    }
-    if y_distance.abs() > 0 {
-        tail_pos.1 += (y_distance).signum();
-    }
-    tail_pos
+    positions.1 + (positions.0 - positions.1).signum()
 }
 
 
 // For Part 2, the follow is called multiple times!
 // Given the positions of head and tail, moving multiple times along segment with the head to follow.
-fn move_chain(positions : &Vec<(i32, i32)>, direction: &Directions) -> Vec<(i32, i32)> {
-    let mut out_positions =  Vec::<(i32, i32)>::new();
-    out_positions.push(move_head(&positions.first().unwrap(), direction));
+fn move_chain(positions : &Vec<Point2>, direction: Dir4) -> Vec<Point2> {
+    let mut out_positions =  Vec::<Point2>::new();
+    out_positions.push(move_head(positions.first().unwrap(), direction));
 
     for idx in 1..positions.len() {
-        out_positions.push(follow_head(&(out_positions.last().unwrap().clone(), positions[idx])));
+        out_positions.push(follow_head(&(*out_positions.last().unwrap(), positions[idx])));
     }
 
     out_positions
 }
 
 
-// Primary Function
-fn execute (input_path : String)  -> Option<(u32, u32)> {
+// Reading the movement commands, one per line like "R 4".
+fn read_commands(input_path : &str) -> Vec<(Dir4, i32)> {
 
     // Handling the reading/parsing
     let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
-    // Results variables:
-    let result_part_1 : u32;
-    let result_part_2 : u32;
-
     // First reading the input string - easy.
-    let mut commands_vect = Vec::<(Directions, i32)>::new();
+    let mut commands_vect = Vec::<(Dir4, i32)>::new();
 
     // Finally reading the stuff.
     for curr_line in reader.lines() {
         if let Ok(line) = curr_line {
             let mut split_line = line.split(' '); // This should not be MUT, but they ask it for the following borrows
             match split_line.next().unwrap() {
-                "U" => commands_vect.push((Directions::U, split_line.next().unwrap().parse::<i32>().unwrap())),
-                "D" => commands_vect.push((Directions::D, split_line.next().unwrap().parse::<i32>().unwrap())),
-                "L" => commands_vect.push((Directions::L, split_line.next().unwrap().parse::<i32>().unwrap())),
-                "R" => commands_vect.push((Directions::R, split_line.next().unwrap().parse::<i32>().unwrap())),
+                "U" => commands_vect.push((Dir4::Up, split_line.next().unwrap().parse::<i32>().unwrap())),
+                "D" => commands_vect.push((Dir4::Down, split_line.next().unwrap().parse::<i32>().unwrap())),
+                "L" => commands_vect.push((Dir4::Left, split_line.next().unwrap().parse::<i32>().unwrap())),
+                "R" => commands_vect.push((Dir4::Right, split_line.next().unwrap().parse::<i32>().unwrap())),
                 _ => panic!("Wrong input syntax!"),
             }
         }
     }
+    commands_vect
+}
+
+
+// The ten knots rope moving one square per step, for the stepper.
+// The first knot behind the head moves exactly like the tail of part 1.
+struct RopeStepper {
+    commands_vect : Vec<(Dir4, i32)>,
+    next_command : usize,
+    moves_done : i32,     // Moves done of the current command.
+    step_counter : u64,
+    knots : Vec<Point2>,
+    knot_1_positions : HashSet<Point2>,
+    tail_positions : HashSet<Point2>,
+}
+
+impl RopeStepper {
+    fn new(commands_vect : Vec<(Dir4, i32)>) -> RopeStepper {
+        RopeStepper {
+            commands_vect,
+            next_command : 0,
+            moves_done : 0,
+            step_counter : 0,
+            knots : vec![Point2::zero(); 10],
+            knot_1_positions : HashSet::from([Point2::zero()]),
+            tail_positions : HashSet::from([Point2::zero()]),
+        }
+    }
+}
+
+// The positions are written "x,y" so that they can be used in the conditions.
+fn position_text(position : &Point2) -> String {
+    format!("{},{}", position.x, position.y)
+}
+
+fn command_text(command : &(Dir4, i32)) -> String {
+    let direction = match command.0 { Dir4::Up => "U", Dir4::Down => "D", Dir4::Left => "L", Dir4::Right => "R" };
+    format!("{}{}", direction, command.1)
+}
+
+impl Steppable for RopeStepper {
+    fn step(&mut self) -> bool {
+        while self.next_command < self.commands_vect.len() && self.moves_done >= self.commands_vect[self.next_command].1 {
+            self.next_command += 1;
+            self.moves_done = 0;
+        }
+        let Some(command) = self.commands_vect.get(self.next_command) else {
+            return false;
+        };
+        self.knots = move_chain(&self.knots, command.0);
+        self.knot_1_positions.insert(self.knots[1]);
+        self.tail_positions.insert(*self.knots.last().unwrap());
+        self.moves_done += 1;
+        self.step_counter += 1;
+        true
+    }
+
+    fn step_number(&self) -> u64 {
+        self.step_counter
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        let command = self.commands_vect.get(self.next_command).map(command_text).unwrap_or("none".to_string());
+        vec![
+            ("command".to_string(), command),
+            ("command_index".to_string(), self.next_command.to_string()),
+            ("head".to_string(), position_text(&self.knots[0])),
+            ("knot_1".to_string(), position_text(&self.knots[1])),
+            ("tail".to_string(), position_text(self.knots.last().unwrap())),
+            ("knot_1_visited".to_string(), self.knot_1_positions.len().to_string()),
+            ("tail_visited".to_string(), self.tail_positions.len().to_string()),
+        ]
+    }
+
+    // The knots around the start, 'H' for the head, digits for the others, 's' for the start.
+    fn draw(&self) -> String {
+        let min_x = self.knots.iter().map(|knot| knot.x).min().unwrap().min(0);
+        let max_x = self.knots.iter().map(|knot| knot.x).max().unwrap().max(0);
+        let min_y = self.knots.iter().map(|knot| knot.y).min().unwrap().min(0);
+        let max_y = self.knots.iter().map(|knot| knot.y).max().unwrap().max(0);
+        let mut rows = Vec::<String>::new();
+        for y in (min_y..=max_y).rev() {
+            let row : String = (min_x..=max_x).map(|x| {
+                match self.knots.iter().position(|knot| *knot == Point2::new(x, y)) {
+                    Some(0) => 'H',
+                    Some(idx) => char::from_digit(idx as u32, 10).unwrap(),
+                    None if x == 0 && y == 0 => 's',
+                    None => '.',
+                }
+            }).collect();
+            rows.push(row);
+        }
+        rows.join("\n")
+    }
+}
+
+
+// Primary Function
+fn execute (input_path : String)  -> Option<(u32, u32)> {
+
+    // Results variables:
+    let result_part_1 : u32;
+    let result_part_2 : u32;
+
+    let commands_vect = read_commands(&input_path);
     println!("read {} lines from input", commands_vect.len());
     assert!(commands_vect.len() > 1);
 
     // Positions
-    let mut head_tail_positions = ((0,0), (0,0));
+    let mut head_tail_positions = (Point2::zero(), Point2::zero());
 
     // tracking all tails positions
-    let mut all_tail_positions = Vec::<(i32,i32)>::new();
+    let mut all_tail_positions = Vec::<Point2>::new();
 
     // Applying the commands, and adding to the vector of all the positions of tail
     for command in &commands_vect {
         for _ in 0..command.1 {
-            let new_positions = move_segment(&head_tail_positions, &command.0, );
+            let new_positions = move_segment(&head_tail_positions, command.0);
             all_tail_positions.push(new_positions.1);
             head_tail_positions = new_positions;
         }
@@ -124,19 +204,20 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     all_tail_positions.sort();
     all_tail_positions.dedup();
     result_part_1 = all_tail_positions.len() as u32;
+    memory::report_part(1);
 
     // For Part 2, we now have TEN knots! 
-    let mut all_links_positions = vec![(0, 0); 10];
+    let mut all_links_positions = vec![Point2::zero(); 10];
 
     // tracking all tails positions
-    let mut all_tail_positions = Vec::<(i32,i32)>::new();
-    all_tail_positions.push((0,0));
+    let mut all_tail_positions = Vec::<Point2>::new();
+    all_tail_positions.push(Point2::zero());
 
     // Applying the commands, and adding to the vector of all the positions of tail
     for command in &commands_vect {
         for _ in 0..command.1 {
-            let new_positions = move_chain(&all_links_positions, &command.0, );
-            all_tail_positions.push(new_positions.last().unwrap().clone());
+            let new_positions = move_chain(&all_links_positions, command.0);
+            all_tail_positions.push(*new_positions.last().unwrap());
             all_links_positions = new_positions;
         }
     }
@@ -146,6 +227,7 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     all_tail_positions.dedup();
     result_part_2 = all_tail_positions.len() as u32;
 
+    memory::report_part(2);
     Some((result_part_1, result_part_2))
 }
 
@@ -154,7 +236,17 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day 9!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    // With --step the rope can be moved interactively instead.
+    let mut args = DayArgs::from_env();
+    let step_mode = args.take_switch("--step");
+    let input_path = args.input_path("./data/input.txt");
+    if step_mode {
+        let mut rope_stepper = RopeStepper::new(read_commands(&input_path));
+        return stepper::run_stepper(&mut rope_stepper, io::stdin().lock(), &mut io::stdout());
+    }
+
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);
@@ -178,4 +270,17 @@ mod tests {
     fn global_test_part_2() {
         assert_eq!(execute("./data/test_2.txt".to_string()).unwrap().1, 36);
     }    
+
+    #[test]
+    fn test_rope_stepper() {
+        let mut rope_stepper = RopeStepper::new(read_commands("./data/test.txt"));
+        stepper::execute_command(&mut rope_stepper, "until head == 4,4").unwrap();
+        assert_eq!(rope_stepper.step_number(), 8);
+        assert_eq!(rope_stepper.knots[1], Point2::new(4, 3));
+
+        stepper::execute_command(&mut rope_stepper, "run 1000").unwrap();
+        assert_eq!(rope_stepper.step_number(), 24);
+        assert_eq!(rope_stepper.knot_1_positions.len(), 13);
+        assert_eq!(rope_stepper.tail_positions.len(), 1);
+    }
 }
\ No newline at end of file
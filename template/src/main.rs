@@ -3,6 +3,7 @@
 // For reading/parsing
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
+use std::env;
 
 // utility
 
@@ -13,23 +14,17 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
     let file = File::open(input_path).unwrap();
     let reader = BufReader::new(file);
 
-    // Results variables:
-    let result_part_1 : u32;
-    let result_part_2 : u32;
-
     // First reading the input string - easy.
     let mut lines_vec = Vec::<String>::new();
     // Finally reading the stuff.
-    for curr_line in reader.lines() {
-        if let Ok(line) = curr_line {
-            lines_vec.push(line);
-        }
+    for line in reader.lines().map_while(Result::ok) {
+        lines_vec.push(line);
     }
     println!("read {} lines from input", lines_vec.len());
     assert!(lines_vec.len() > 1);
 
-    result_part_1 = 0;
-    result_part_2 = 0;
+    let result_part_1 = 0;
+    let result_part_2 = 0;
     Some((result_part_1, result_part_2))
 }
 
@@ -37,7 +32,9 @@ fn execute (input_path : String)  -> Option<(u32, u32)> {
 fn main() -> io::Result<()> {
     println!("Welcome to Advent of Code 2022 - Day XXX!");
 
-    let results = execute("./data/input.txt".to_string()).unwrap();
+    // The input file can be passed as first argument, the default is the puzzle input.
+    let input_path = env::args().nth(1).unwrap_or("./data/input.txt".to_string());
+    let results = execute(input_path).unwrap();
     
     println!("Part 1 result is {}.", results.0);
     println!("Part 2 result is {}.", results.1);